@@ -0,0 +1,31 @@
+//! Decodes arbitrary FIFO contents, e.g. misaligned after an overflow.
+//! The first byte selects FIFO_EN, the second the decimation, the rest is FIFO data.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mpu6000::fifo::{self, FifoFrames, Timestamp};
+use mpu6000::{FifoEnable, Orientation, Rotation, SensorDecimation};
+
+fuzz_target!(|data: &[u8]| {
+    let (config, decimation, bytes) = match data {
+        [config, decimation, bytes @ ..] => (FifoEnable::from(*config), *decimation, bytes),
+        _ => return,
+    };
+    let size = fifo::frame_size(&config);
+    let count = bytes.len().checked_div(size).unwrap_or(0);
+
+    let decimation = SensorDecimation::new((decimation >> 4) as u16, (decimation & 0xf) as u16);
+    let orientation = Orientation { rotation: Rotation::Roll180Yaw90, ..Default::default() };
+    let drain_time = Timestamp::from_ticks(u64::MAX);
+    let frames = FifoFrames::with_timestamps(&config, bytes, drain_time, 0)
+        .with_orientation(orientation)
+        .with_decimation(decimation);
+    assert_eq!(frames.size_hint(), (count, Some(count)));
+    assert_eq!(frames.count(), count);
+
+    for start in (0..bytes.len()).step_by(size.max(1)) {
+        let whole = size > 0 && start + size <= bytes.len();
+        assert_eq!(fifo::decode_frame(&config, &bytes[start..]).is_some(), whole);
+    }
+});
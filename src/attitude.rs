@@ -0,0 +1,337 @@
+//! Simple attitude helpers built on top of scaled readings
+
+use fugit::Duration;
+
+/// Wraps an angle in degrees into [-180, 180)
+pub fn wrap_degrees(angle: f32) -> f32 {
+    let wrapped = (angle + 180.0) % 360.0;
+    if wrapped < 0.0 {
+        wrapped + 180.0
+    } else {
+        wrapped - 180.0
+    }
+}
+
+/// Accumulates gyro rates in °/s into roll/pitch/yaw angles in degrees.
+///
+/// Each axis is integrated independently, which is only accurate for rotation
+/// around a single axis at a time, e.g. heading hold or turntables.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AngleIntegrator {
+    angles: (f32, f32, f32),
+    dt: f32,
+}
+
+impl AngleIntegrator {
+    /// `sample_rate` in Hz, used by `update`
+    pub fn new(sample_rate: f32) -> Self {
+        Self { angles: (0.0, 0.0, 0.0), dt: 1.0 / sample_rate }
+    }
+
+    /// Integrates one sample taken at the configured sample rate
+    pub fn update(&mut self, rates: (f32, f32, f32)) -> (f32, f32, f32) {
+        self.update_dt(rates, self.dt)
+    }
+
+    /// Integrates one sample taken `dt` seconds after the previous one
+    pub fn update_dt(&mut self, rates: (f32, f32, f32), dt: f32) -> (f32, f32, f32) {
+        let (roll, pitch, yaw) = self.angles;
+        self.angles = (
+            wrap_degrees(roll + rates.0 * dt),
+            wrap_degrees(pitch + rates.1 * dt),
+            wrap_degrees(yaw + rates.2 * dt),
+        );
+        self.angles
+    }
+
+    /// Integrates one sample taken `dt` after the previous one
+    pub fn update_duration<const NOM: u32, const DENOM: u32>(
+        &mut self,
+        rates: (f32, f32, f32),
+        dt: Duration<u32, NOM, DENOM>,
+    ) -> (f32, f32, f32) {
+        self.update_dt(rates, dt.to_micros() as f32 / 1_000_000.0)
+    }
+
+    /// (roll, pitch, yaw) in degrees, each within [-180, 180)
+    pub fn angles(&self) -> (f32, f32, f32) {
+        self.angles
+    }
+
+    pub fn zero(&mut self) {
+        self.angles = (0.0, 0.0, 0.0)
+    }
+
+    /// Overrides accumulated angles, e.g. with an absolute reference
+    pub fn correct(&mut self, angles: (f32, f32, f32)) {
+        self.angles = (wrap_degrees(angles.0), wrap_degrees(angles.1), wrap_degrees(angles.2))
+    }
+}
+
+/// Mounting tilt in degrees, as measured by `MPU6000::measure_level`
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LevelTrim {
+    pub roll: f32,
+    pub pitch: f32,
+}
+
+impl LevelTrim {
+    /// Trim from an accelerometer vector measured while level and at rest, any unit
+    pub fn from_gravity(gravity: (f32, f32, f32)) -> Self {
+        let (x, y, z) = gravity;
+        let roll = libm::atan2f(y, z);
+        let pitch = libm::atan2f(-x, libm::sqrtf(y * y + z * z));
+        Self { roll: roll.to_degrees(), pitch: pitch.to_degrees() }
+    }
+
+    /// Rotates a sensor frame vector into the levelled frame
+    pub fn apply(&self, vector: (f32, f32, f32)) -> (f32, f32, f32) {
+        let (sin_roll, cos_roll) = libm::sincosf(self.roll.to_radians());
+        let (sin_pitch, cos_pitch) = libm::sincosf(self.pitch.to_radians());
+        let (x, y, z) = vector;
+        let (y, z) = (cos_roll * y - sin_roll * z, sin_roll * y + cos_roll * z);
+        (cos_pitch * x + sin_pitch * z, y, -sin_pitch * x + cos_pitch * z)
+    }
+}
+
+/// Sensor axis pointing up, i.e. reading +1g at rest
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GravityAxis {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl GravityAxis {
+    /// Dominant axis of an accelerometer vector at rest, `None` if no axis carries
+    /// at least 90% of the magnitude, e.g. on a tilted board
+    pub fn from_gravity(gravity: (f32, f32, f32)) -> Option<Self> {
+        let (x, y, z) = gravity;
+        let magnitude = libm::sqrtf(x * x + y * y + z * z);
+        let candidates = [
+            (x, GravityAxis::PositiveX, GravityAxis::NegativeX),
+            (y, GravityAxis::PositiveY, GravityAxis::NegativeY),
+            (z, GravityAxis::PositiveZ, GravityAxis::NegativeZ),
+        ];
+        let threshold = magnitude * 0.9;
+        candidates
+            .iter()
+            .find(|(value, _, _)| value.abs() >= threshold && magnitude > 0.0)
+            .map(|&(value, positive, negative)| if value > 0.0 { positive } else { negative })
+    }
+}
+
+/// Unit rotation quaternion, `w + xi + yj + zk`
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quaternion {
+    pub w: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Quaternion {
+    pub const IDENTITY: Self = Self { w: 1.0, x: 0.0, y: 0.0, z: 0.0 };
+
+    pub fn new(w: f32, x: f32, y: f32, z: f32) -> Self {
+        Self { w, x, y, z }
+    }
+
+    /// From roll, pitch and yaw in degrees, applied in yaw, pitch, roll order
+    pub fn from_euler(roll: f32, pitch: f32, yaw: f32) -> Self {
+        Self::from_euler_radians(roll.to_radians(), pitch.to_radians(), yaw.to_radians())
+    }
+
+    /// `from_euler` with angles in radians
+    pub fn from_euler_radians(roll: f32, pitch: f32, yaw: f32) -> Self {
+        let (sr, cr) = libm::sincosf(roll / 2.0);
+        let (sp, cp) = libm::sincosf(pitch / 2.0);
+        let (sy, cy) = libm::sincosf(yaw / 2.0);
+        Self {
+            w: cr * cp * cy + sr * sp * sy,
+            x: sr * cp * cy - cr * sp * sy,
+            y: cr * sp * cy + sr * cp * sy,
+            z: cr * cp * sy - sr * sp * cy,
+        }
+    }
+
+    pub fn norm(&self) -> f32 {
+        libm::sqrtf(self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z)
+    }
+
+    /// Unit length copy, identity for a zero quaternion
+    pub fn normalize(&self) -> Self {
+        let norm = self.norm();
+        if norm == 0.0 {
+            return Self::IDENTITY;
+        }
+        Self { w: self.w / norm, x: self.x / norm, y: self.y / norm, z: self.z / norm }
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Self { w: self.w, x: -self.x, y: -self.y, z: -self.z }
+    }
+
+    /// Hamilton product, the rotation `other` followed by `self`
+    pub fn multiply(&self, other: &Self) -> Self {
+        let (a, b) = (self, other);
+        Self {
+            w: a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+            x: a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            y: a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            z: a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+        }
+    }
+
+    /// Rotates `vector` by this unit quaternion
+    pub fn rotate(&self, vector: (f32, f32, f32)) -> (f32, f32, f32) {
+        let v = Self { w: 0.0, x: vector.0, y: vector.1, z: vector.2 };
+        let r = self.multiply(&v).multiply(&self.conjugate());
+        (r.x, r.y, r.z)
+    }
+
+    /// (roll, pitch, yaw) in degrees, pitch clamped to +/- 90 at the poles
+    pub fn to_euler(&self) -> (f32, f32, f32) {
+        let (roll, pitch, yaw) = self.to_euler_radians();
+        (roll.to_degrees(), pitch.to_degrees(), yaw.to_degrees())
+    }
+
+    /// `to_euler` in radians
+    pub fn to_euler_radians(&self) -> (f32, f32, f32) {
+        let Self { w, x, y, z } = *self;
+        let roll = libm::atan2f(2.0 * (w * x + y * z), 1.0 - 2.0 * (x * x + y * y));
+        let pitch = libm::asinf((2.0 * (w * y - z * x)).clamp(-1.0, 1.0));
+        let yaw = libm::atan2f(2.0 * (w * z + x * y), 1.0 - 2.0 * (y * y + z * z));
+        (roll, pitch, yaw)
+    }
+}
+
+/// Accelerometer reading in g with gravity removed, given the attitude from the
+/// sensor frame to a Z up earth frame, e.g. from `Quaternion::from_euler`.
+/// The result stays in the sensor frame, rotate it with `attitude` for earth axes.
+pub fn linear_acceleration(
+    attitude: &Quaternion,
+    acceleration: (f32, f32, f32),
+) -> (f32, f32, f32) {
+    let (gx, gy, gz) = attitude.conjugate().rotate((0.0, 0.0, 1.0));
+    (acceleration.0 - gx, acceleration.1 - gy, acceleration.2 - gz)
+}
+
+/// (roll, pitch, yaw) in degrees to a quaternion, see `Quaternion::from_euler`
+pub fn euler_to_quaternion(euler: (f32, f32, f32)) -> Quaternion {
+    Quaternion::from_euler(euler.0, euler.1, euler.2)
+}
+
+/// (roll, pitch, yaw) in radians to a quaternion
+pub fn euler_radians_to_quaternion(euler: (f32, f32, f32)) -> Quaternion {
+    Quaternion::from_euler_radians(euler.0, euler.1, euler.2)
+}
+
+/// (roll, pitch, yaw) in degrees, see `Quaternion::to_euler`
+pub fn quaternion_to_euler(quaternion: &Quaternion) -> (f32, f32, f32) {
+    quaternion.to_euler()
+}
+
+/// (roll, pitch, yaw) in radians
+pub fn quaternion_to_euler_radians(quaternion: &Quaternion) -> (f32, f32, f32) {
+    quaternion.to_euler_radians()
+}
+
+impl core::ops::Mul for Quaternion {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        self.multiply(&other)
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<Quaternion> for mint::Quaternion<f32> {
+    fn from(q: Quaternion) -> Self {
+        mint::Quaternion { s: q.w, v: mint::Vector3 { x: q.x, y: q.y, z: q.z } }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_angle_integrator() {
+        use super::AngleIntegrator;
+
+        let mut integrator = AngleIntegrator::new(100.0);
+        for _ in 0..100 {
+            integrator.update((90.0, 0.0, -270.0));
+        }
+        let (roll, pitch, yaw) = integrator.angles();
+        assert!((roll - 90.0).abs() < 1e-3);
+        assert_eq!(pitch, 0.0);
+        assert!((yaw - 90.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_level_trim() {
+        use super::LevelTrim;
+
+        let gravity = (-0.2, 0.3, 0.93);
+        let trim = LevelTrim::from_gravity(gravity);
+        let (x, y, z) = trim.apply(gravity);
+        assert!(x.abs() < 1e-5 && y.abs() < 1e-5);
+        assert!((z - libm::sqrtf(0.04 + 0.09 + 0.93 * 0.93)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_quaternion() {
+        use super::{euler_radians_to_quaternion, quaternion_to_euler_radians};
+        use super::{euler_to_quaternion, quaternion_to_euler, Quaternion};
+
+        let q = Quaternion::from_euler(10.0, -20.0, 30.0);
+        let (roll, pitch, yaw) = q.to_euler();
+        assert!((roll - 10.0).abs() < 1e-3 && (pitch + 20.0).abs() < 1e-3);
+        assert!((yaw - 30.0).abs() < 1e-3);
+
+        let yaw90 = Quaternion::from_euler(0.0, 0.0, 90.0);
+        let (x, y, z) = yaw90.rotate((1.0, 0.0, 0.0));
+        assert!(x.abs() < 1e-6 && (y - 1.0).abs() < 1e-6 && z.abs() < 1e-6);
+        let (_, _, yaw) = (yaw90 * yaw90).normalize().to_euler();
+        assert!((yaw.abs() - 180.0).abs() < 1e-3);
+
+        let euler = (0.5, -0.25, 3.0);
+        let (roll, pitch, yaw) = quaternion_to_euler_radians(&euler_radians_to_quaternion(euler));
+        assert!((roll - 0.5).abs() < 1e-5 && (pitch + 0.25).abs() < 1e-5);
+        assert!((yaw - 3.0).abs() < 1e-5);
+        assert_eq!(quaternion_to_euler(&euler_to_quaternion((10.0, 0.0, 0.0))).1, 0.0);
+    }
+
+    #[test]
+    fn test_gravity_axis() {
+        use super::GravityAxis;
+
+        assert_eq!(GravityAxis::from_gravity((0.1, -0.05, 0.98)), Some(GravityAxis::PositiveZ));
+        assert_eq!(GravityAxis::from_gravity((0.0, -1.0, 0.02)), Some(GravityAxis::NegativeY));
+        assert_eq!(GravityAxis::from_gravity((0.7, 0.7, 0.0)), None);
+    }
+
+    #[test]
+    fn test_linear_acceleration() {
+        use super::{linear_acceleration, LevelTrim, Quaternion};
+
+        let gravity = (-0.2, 0.3, libm::sqrtf(1.0 - 0.04 - 0.09));
+        let trim = LevelTrim::from_gravity(gravity);
+        let attitude = Quaternion::from_euler(trim.roll, trim.pitch, 42.0);
+        let (x, y, z) = linear_acceleration(&attitude, gravity);
+        assert!(x.abs() < 1e-5 && y.abs() < 1e-5 && z.abs() < 1e-5);
+        let (x, _, _) = linear_acceleration(&Quaternion::IDENTITY, (0.5, 0.0, 1.0));
+        assert_eq!(x, 0.5);
+    }
+}
@@ -0,0 +1,147 @@
+//! Opt-in automatic range switching driven by clipping detection
+
+/// Configures automatic range switching, see `MPU6000::set_auto_range`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AutoRange {
+    /// Consecutive clipped samples before stepping up to the next larger range
+    pub escalate_after: u16,
+    /// Consecutive samples below a quarter of full scale before stepping back down,
+    /// `None` keeps the escalated range
+    pub relax_after: Option<u16>,
+}
+
+impl Default for AutoRange {
+    fn default() -> Self {
+        Self { escalate_after: 4, relax_after: None }
+    }
+}
+
+pub(crate) enum Step {
+    Up,
+    Down,
+}
+
+#[derive(Default)]
+pub(crate) struct RangeTracker {
+    clipped: u16,
+    quiet: u16,
+}
+
+impl RangeTracker {
+    pub(crate) fn update(&mut self, config: &AutoRange, axes: [i16; 3]) -> Option<Step> {
+        let clipped = axes.iter().any(|&v| v == i16::MAX || v <= -i16::MAX);
+        let quiet = axes.iter().all(|&v| (v as i32).abs() < i16::MAX as i32 / 4);
+        self.clipped = if clipped { self.clipped.saturating_add(1) } else { 0 };
+        self.quiet = if quiet { self.quiet.saturating_add(1) } else { 0 };
+        if self.clipped >= config.escalate_after {
+            *self = Self::default();
+            return Some(Step::Up);
+        }
+        match config.relax_after {
+            Some(samples) if self.quiet >= samples => {
+                *self = Self::default();
+                Some(Step::Down)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AutoRange, RangeTracker, Step};
+
+    const CLIPPED: [i16; 3] = [i16::MAX, 0, 0];
+    const QUIET: [i16; 3] = [100, -100, 0];
+    const MODERATE: [i16; 3] = [16384, 0, 0];
+
+    #[test]
+    fn test_step_up_after_clipping() {
+        let config = AutoRange { escalate_after: 3, relax_after: None };
+        let mut tracker = RangeTracker::default();
+        assert!(tracker.update(&config, CLIPPED).is_none());
+        assert!(tracker.update(&config, [0, -i16::MAX, 0]).is_none());
+        assert!(matches!(tracker.update(&config, CLIPPED), Some(Step::Up)));
+        assert!(tracker.update(&config, CLIPPED).is_none());
+    }
+
+    #[test]
+    fn test_intermittent_clipping() {
+        let config = AutoRange { escalate_after: 3, relax_after: None };
+        let mut tracker = RangeTracker::default();
+        for _ in 0..8 {
+            assert!(tracker.update(&config, CLIPPED).is_none());
+            assert!(tracker.update(&config, CLIPPED).is_none());
+            assert!(tracker.update(&config, MODERATE).is_none());
+        }
+    }
+
+    #[test]
+    fn test_step_down() {
+        let config = AutoRange { escalate_after: 3, relax_after: Some(4) };
+        let mut tracker = RangeTracker::default();
+        for _ in 0..3 {
+            assert!(tracker.update(&config, QUIET).is_none());
+        }
+        assert!(tracker.update(&config, MODERATE).is_none());
+        for _ in 0..3 {
+            assert!(tracker.update(&config, QUIET).is_none());
+        }
+        assert!(matches!(tracker.update(&config, QUIET), Some(Step::Down)));
+
+        let config = AutoRange { relax_after: None, ..config };
+        for _ in 0..8 {
+            assert!(tracker.update(&config, QUIET).is_none());
+        }
+    }
+
+    /// Every sensor axis reads `.0`, counts register writes in `.1`
+    struct AxisBus(i16, u8);
+
+    impl crate::bus::RegAccess for AxisBus {
+        type Error = ();
+
+        fn write(&mut self, _reg: crate::registers::Register, _value: u8) -> Result<(), ()> {
+            self.1 += 1;
+            Ok(())
+        }
+
+        fn read(&mut self, _reg: crate::registers::Register) -> Result<u8, ()> {
+            Ok(0)
+        }
+
+        fn reads(&mut self, _reg: crate::registers::Register, output: &mut [u8]) -> Result<(), ()> {
+            for pair in output.chunks_mut(2) {
+                pair.copy_from_slice(&self.0.to_be_bytes()[..pair.len()]);
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        use crate::registers::{AccelerometerRange, GyroRange};
+        use crate::MPU6000;
+
+        let config = AutoRange { escalate_after: 2, relax_after: Some(2) };
+        let mut mpu6000 = MPU6000::new(AxisBus(i16::MAX, 0));
+        mpu6000.set_accelerometer_range(AccelerometerRange::G16).ok();
+        mpu6000.set_gyro_range(GyroRange::DPS2000).ok();
+        mpu6000.set_auto_range(Some(config));
+        for _ in 0..4 {
+            mpu6000.read_all().ok();
+        }
+        assert_eq!(mpu6000.accelerometer_range(), AccelerometerRange::G16);
+        assert_eq!(mpu6000.gyro_range(), GyroRange::DPS2000);
+        assert_eq!(mpu6000.free().1, 2);
+
+        let mut mpu6000 = MPU6000::new(AxisBus(0, 0));
+        mpu6000.set_auto_range(Some(config));
+        for _ in 0..4 {
+            mpu6000.read_all().ok();
+        }
+        assert_eq!(mpu6000.accelerometer_range(), AccelerometerRange::G2);
+        assert_eq!(mpu6000.gyro_range(), GyroRange::DPS250);
+        assert_eq!(mpu6000.free().1, 0);
+    }
+}
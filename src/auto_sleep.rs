@@ -0,0 +1,107 @@
+//! Inactivity based transition to wake on motion and back
+
+use embedded_hal::blocking::delay::DelayMs;
+use fugit::MillisDurationU32;
+
+use crate::bus::RegAccess;
+use crate::error::Error;
+use crate::profile::Profile;
+use crate::registers::WakeRate;
+use crate::stats::Stats;
+use crate::MPU6000;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AutoSleepConfig {
+    /// How long the device must be still before sleeping
+    pub idle_time: MillisDurationU32,
+    /// Per axis accelerometer variance in g² below which the device counts as still
+    pub max_variance: f32,
+    /// Wake up threshold, see `MPU6000::enter_wake_on_motion`
+    pub threshold_mg: u16,
+    pub wake_rate: WakeRate,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PowerEvent {
+    /// Entered wake on motion after `idle_time` without movement
+    Sleeping,
+    /// Left wake on motion after a motion interrupt
+    Awake,
+}
+
+/// Puts the device into wake on motion after a period of stillness, judged over a
+/// window of the last `N` accelerometer samples.
+///
+/// Feed every scaled accelerometer reading to `update` and call `on_motion` when the
+/// motion interrupt fires while sleeping.
+#[derive(Copy, Clone, Debug)]
+pub struct AutoSleep<const N: usize> {
+    config: AutoSleepConfig,
+    stats: Stats<N>,
+    idle_us: u32,
+    sleeping: bool,
+}
+
+impl<const N: usize> AutoSleep<N> {
+    pub fn new(config: AutoSleepConfig) -> Self {
+        Self { config, stats: Stats::new(), idle_us: 0, sleeping: false }
+    }
+
+    pub fn is_sleeping(&self) -> bool {
+        self.sleeping
+    }
+
+    /// Accounts one sample in g taken at the device sample rate, entering wake on
+    /// motion once still for `idle_time`
+    pub fn update<E, BUS, P, D>(
+        &mut self,
+        mpu6000: &mut MPU6000<BUS, P>,
+        acceleration: (f32, f32, f32),
+        delay: &mut D,
+    ) -> Result<Option<PowerEvent>, Error<E>>
+    where
+        BUS: RegAccess<Error = E>,
+        P: Profile,
+        D: DelayMs<u32>,
+    {
+        if self.sleeping {
+            return Ok(None);
+        }
+        self.stats.update(acceleration);
+        if !self.stats.is_still(self.config.max_variance) {
+            self.idle_us = 0;
+            return Ok(None);
+        }
+        let period = 1_000_000 / mpu6000.sample_rate() as u32;
+        self.idle_us = self.idle_us.saturating_add(period);
+        if self.idle_us < self.config.idle_time.to_micros() {
+            return Ok(None);
+        }
+        let AutoSleepConfig { threshold_mg, wake_rate, .. } = self.config;
+        mpu6000.enter_wake_on_motion(threshold_mg, wake_rate, delay)?;
+        debug!("auto sleep after {}us idle", self.idle_us);
+        self.sleeping = true;
+        Ok(Some(PowerEvent::Sleeping))
+    }
+
+    /// Leaves wake on motion, call on the motion interrupt
+    pub fn on_motion<E, BUS, P, D>(
+        &mut self,
+        mpu6000: &mut MPU6000<BUS, P>,
+        delay: &mut D,
+    ) -> Result<Option<PowerEvent>, E>
+    where
+        BUS: RegAccess<Error = E>,
+        P: Profile,
+        D: DelayMs<u32>,
+    {
+        if !self.sleeping {
+            return Ok(None);
+        }
+        mpu6000.exit_wake_on_motion(delay)?;
+        self.sleeping = false;
+        self.idle_us = 0;
+        self.stats.reset();
+        Ok(Some(PowerEvent::Awake))
+    }
+}
@@ -6,6 +6,7 @@ use embedded_hal::digital::v2::OutputPin;
 
 use super::registers::Register;
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SpiError<WE, TE, OE> {
     WriteError(WE),
     TransferError(TE),
@@ -16,6 +17,8 @@ pub struct SpiBus<BUS, CS, DELAY> {
     bus: BUS,
     cs: CS,
     delay: DELAY,
+    cs_active_high: bool,
+    max_burst: usize,
 }
 
 pub trait RegAccess {
@@ -25,6 +28,27 @@ pub trait RegAccess {
     fn reads(&mut self, reg: Register, output: &mut [u8]) -> Result<(), Self::Error>;
 }
 
+/// Reads `output` in transactions of at most `max_burst` bytes, advancing the address
+/// between them except for the FIFO data register, which is read in place
+fn split_reads<E, F>(
+    reg: Register,
+    output: &mut [u8],
+    max_burst: usize,
+    mut read: F,
+) -> Result<(), E>
+where
+    F: FnMut(u8, &mut [u8]) -> Result<(), E>,
+{
+    let mut address = reg as u8;
+    for chunk in output.chunks_mut(max_burst.max(1)) {
+        read(address, chunk)?;
+        if reg != Register::FifoReadWrite {
+            address = address.wrapping_add(chunk.len() as u8);
+        }
+    }
+    Ok(())
+}
+
 impl<WE, TE, OE, SPI, CS, DELAY> SpiBus<SPI, CS, DELAY>
 where
     SPI: spi::Write<u8, Error = WE> + spi::Transfer<u8, Error = TE>,
@@ -32,11 +56,16 @@ where
     DELAY: DelayUs<u8>,
 {
     pub fn new(spi: SPI, cs: CS, delay: DELAY) -> Self {
-        Self { bus: spi, cs, delay }
+        Self::with_cs_polarity(spi, cs, delay, false)
+    }
+
+    /// `cs_active_high` for chip select routed through an inverting level shifter
+    pub fn with_cs_polarity(spi: SPI, cs: CS, delay: DELAY, cs_active_high: bool) -> Self {
+        Self { bus: spi, cs, delay, cs_active_high, max_burst: usize::MAX }
     }
 
     fn chip_select(&mut self, select: bool) -> Result<(), SpiError<WE, TE, OE>> {
-        if select { self.cs.set_low() } else { self.cs.set_high() }
+        if select != self.cs_active_high { self.cs.set_low() } else { self.cs.set_high() }
             .map_err(|e| SpiError::OutputPinError(e))
     }
 }
@@ -45,6 +74,25 @@ impl<SPI, CS, DELAY> SpiBus<SPI, CS, DELAY> {
     pub fn free(self) -> (SPI, CS, DELAY) {
         (self.bus, self.cs, self.delay)
     }
+
+    /// Longest read in one transaction, e.g. the DMA buffer size of the HAL. Longer
+    /// reads are split, unlimited by default.
+    pub fn set_max_burst(&mut self, max_burst: usize) {
+        self.max_burst = max_burst
+    }
+
+    pub fn max_burst(&self) -> usize {
+        self.max_burst
+    }
+
+    pub fn bus(&self) -> &SPI {
+        &self.bus
+    }
+
+    /// For out-of-band operations like changing the clock speed, chip select is untouched
+    pub fn bus_mut(&mut self) -> &mut SPI {
+        &mut self.bus
+    }
 }
 
 impl<WE, TE, OE, SPI, CS, DELAY> RegAccess for SpiBus<SPI, CS, DELAY>
@@ -56,12 +104,16 @@ where
     type Error = SpiError<WE, TE, OE>;
 
     fn write(&mut self, reg: Register, value: u8) -> Result<(), Self::Error> {
+        trace!("write {:?} {:#x}", reg, value);
         self.chip_select(true)?;
         self.delay.delay_us(1);
         let result = self.bus.write(&[reg as u8, value]);
         self.chip_select(false)?;
         self.delay.delay_us(1);
-        result.map_err(|e| Self::Error::WriteError(e))
+        result.map_err(|e| {
+            error!("spi write to {:?} failed", reg);
+            Self::Error::WriteError(e)
+        })
     }
 
     fn read(&mut self, reg: Register) -> Result<u8, Self::Error> {
@@ -71,46 +123,105 @@ where
     }
 
     fn reads(&mut self, reg: Register, output: &mut [u8]) -> Result<(), Self::Error> {
-        self.chip_select(true)?;
-        self.delay.delay_us(1);
-        self.bus.write(&[reg as u8 | 0x80]).map_err(|e| Self::Error::WriteError(e))?;
-        self.bus.transfer(output).map_err(|e| Self::Error::TransferError(e))?;
-        self.chip_select(false)?;
-        self.delay.delay_us(1);
-        Ok(())
+        trace!("read {:?} {} bytes", reg, output.len());
+        let max_burst = self.max_burst;
+        split_reads(reg, output, max_burst, |address, output| {
+            self.chip_select(true)?;
+            self.delay.delay_us(1);
+            self.bus.write(&[address | 0x80]).map_err(|e| {
+                error!("spi write to {:?} failed", reg);
+                SpiError::WriteError(e)
+            })?;
+            self.bus.transfer(output).map_err(|e| {
+                error!("spi transfer from {:?} failed", reg);
+                SpiError::TransferError(e)
+            })?;
+            self.chip_select(false)?;
+            self.delay.delay_us(1);
+            Ok(())
+        })
+    }
+}
+
+/// I2C address with the AD0 pin tied low
+pub const MPU6050_ADDR_AD0_LOW: u8 = 0x68;
+/// I2C address with the AD0 pin tied high
+pub const MPU6050_ADDR_AD0_HIGH: u8 = 0x69;
+
+/// Level of the AD0 pin, selecting the I2C address
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Ad0Level {
+    Low,
+    High,
+}
+
+impl Ad0Level {
+    pub fn address(&self) -> u8 {
+        match self {
+            Ad0Level::Low => MPU6050_ADDR_AD0_LOW,
+            Ad0Level::High => MPU6050_ADDR_AD0_HIGH,
+        }
     }
 }
 
-pub struct I2cBus<BUS, DELAY> {
+pub struct I2cBus<BUS> {
     bus: BUS,
     address: u8,
-    delay: DELAY,
+    max_burst: usize,
 }
 
-impl<E, I2C, DELAY> I2cBus<I2C, DELAY>
+impl<E, I2C> I2cBus<I2C>
 where
     I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E>,
 {
-    pub fn i2c(i2c: I2C, address: u8, delay: DELAY) -> Self {
-        Self { bus: i2c, address, delay }
+    pub fn with_ad0(i2c: I2C, ad0: Ad0Level) -> Self {
+        Self { bus: i2c, address: ad0.address(), max_burst: usize::MAX }
     }
 }
 
-impl<I2C, DELAY> I2cBus<I2C, DELAY> {
-    pub fn free(self) -> (I2C, DELAY) {
-        (self.bus, self.delay)
+impl<I2C> I2cBus<I2C> {
+    pub fn free(self) -> I2C {
+        self.bus
+    }
+
+    /// 7-bit device address
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Longest read in one transaction, e.g. the transfer limit of the controller.
+    /// Longer reads are split, unlimited by default.
+    pub fn set_max_burst(&mut self, max_burst: usize) {
+        self.max_burst = max_burst
+    }
+
+    pub fn max_burst(&self) -> usize {
+        self.max_burst
+    }
+
+    pub fn bus(&self) -> &I2C {
+        &self.bus
+    }
+
+    /// For out-of-band operations like bus recovery or talking to other devices
+    pub fn bus_mut(&mut self) -> &mut I2C {
+        &mut self.bus
     }
 }
 
-impl<E, I2C, DELAY> RegAccess for I2cBus<I2C, DELAY>
+impl<E, I2C> RegAccess for I2cBus<I2C>
 where
     I2C: i2c::Write<Error = E> + i2c::WriteRead<Error = E>,
-    DELAY: DelayUs<u8>,
 {
     type Error = E;
 
     fn write(&mut self, reg: Register, value: u8) -> Result<(), Self::Error> {
-        self.bus.write(self.address, &[reg as u8, value])
+        trace!("write {:?} {:#x}", reg, value);
+        self.bus.write(self.address, &[reg as u8, value]).map_err(|e| {
+            error!("i2c write to {:?} failed", reg);
+            e
+        })
     }
 
     fn read(&mut self, reg: Register) -> Result<u8, Self::Error> {
@@ -120,6 +231,55 @@ where
     }
 
     fn reads(&mut self, reg: Register, output: &mut [u8]) -> Result<(), Self::Error> {
-        self.bus.write_read(self.address, &[reg as u8 | 0x80], output)
+        trace!("read {:?} {} bytes", reg, output.len());
+        let (bus, device) = (&mut self.bus, self.address);
+        split_reads(reg, output, self.max_burst, |address, output| {
+            bus.write_read(device, &[address | 0x80], output).map_err(|e| {
+                error!("i2c read from {:?} failed", reg);
+                e
+            })
+        })
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<WE: ufmt::uDebug, TE: ufmt::uDebug, OE: ufmt::uDebug> ufmt::uDebug for SpiError<WE, TE, OE> {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        match self {
+            Self::WriteError(e) => f.debug_tuple("WriteError")?.field(e)?.finish(),
+            Self::TransferError(e) => f.debug_tuple("TransferError")?.field(e)?.finish(),
+            Self::OutputPinError(e) => f.debug_tuple("OutputPinError")?.field(e)?.finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_split_reads() {
+        use super::{split_reads, Register};
+
+        let mut reads = [(0u8, 0usize); 3];
+        let mut count = 0;
+        let mut output = [0u8; 14];
+        let result: Result<(), ()> =
+            split_reads(Register::AccelerometerXHigh, &mut output, 6, |address, output| {
+                reads[count] = (address, output.len());
+                count += 1;
+                Ok(())
+            });
+        assert!(result.is_ok());
+        assert_eq!(reads, [(0x3b, 6), (0x41, 6), (0x47, 2)]);
+        count = 0;
+        let _: Result<(), ()> =
+            split_reads(Register::FifoReadWrite, &mut output, 8, |address, output| {
+                reads[count] = (address, output.len());
+                count += 1;
+                Ok(())
+            });
+        assert_eq!(reads[..2], [(0x74, 8), (0x74, 6)]);
     }
 }
@@ -0,0 +1,226 @@
+//! Calibration results applied by the driver to scaled reads
+
+use crate::attitude::LevelTrim;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CalibrationData {
+    /// Mounting tilt correction, see `MPU6000::measure_level`
+    pub level_trim: Option<LevelTrim>,
+    /// Gyro zero rate offset in °/s, subtracted from `MPU6000::read_gyro` before the trim
+    pub gyro_bias: Option<(f32, f32, f32)>,
+    /// Temperature dependent zero rate offset, takes over from `gyro_bias` once the
+    /// driver has read a temperature, see `MPU6000::capture_temperature_sweep`.
+    /// `MPU6000::calibrate` and `MPU6000::set_gyro_bias` clear it.
+    pub gyro_temperature_model: Option<GyroTemperatureModel>,
+}
+
+type Vector = (f32, f32, f32);
+
+/// Temperature span a sweep must cover before `TemperatureSweep::fit` trusts a slope
+const MIN_SWEEP_SPAN: f32 = 1.0;
+
+/// Linear gyro zero rate offset over temperature, `offset + slope * °C` in °/s
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GyroTemperatureModel {
+    pub offset: Vector,
+    /// °/s per °C
+    pub slope: Vector,
+}
+
+impl GyroTemperatureModel {
+    /// Zero rate offset in °/s at `celsius`
+    pub fn bias(&self, celsius: f32) -> Vector {
+        let (offset, slope) = (self.offset, self.slope);
+        (offset.0 + slope.0 * celsius, offset.1 + slope.1 * celsius, offset.2 + slope.2 * celsius)
+    }
+}
+
+/// Least squares fit of gyro rate readings taken at rest against temperature,
+/// kept as running sums so a long warm-up needs no sample buffer
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TemperatureSweep {
+    /// Temperatures are summed relative to the first reading to keep f32 precision
+    reference: Option<f32>,
+    count: u32,
+    min: f32,
+    max: f32,
+    sum_t: f32,
+    sum_tt: f32,
+    sum_b: [f32; 3],
+    sum_tb: [f32; 3],
+}
+
+impl TemperatureSweep {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a reading of the uncorrected rate in °/s at `celsius`
+    pub fn update(&mut self, celsius: f32, rates: Vector) {
+        let reference = *self.reference.get_or_insert(celsius);
+        let t = celsius - reference;
+        if self.count == 0 {
+            self.min = t;
+            self.max = t;
+        }
+        self.min = self.min.min(t);
+        self.max = self.max.max(t);
+        self.count += 1;
+        self.sum_t += t;
+        self.sum_tt += t * t;
+        for (i, rate) in [rates.0, rates.1, rates.2].iter().enumerate() {
+            self.sum_b[i] += rate;
+            self.sum_tb[i] += t * rate;
+        }
+    }
+
+    /// Temperature range covered so far in °C
+    pub fn span(&self) -> f32 {
+        self.max - self.min
+    }
+
+    /// `None` until the readings cover at least 1°C
+    pub fn fit(&self) -> Option<GyroTemperatureModel> {
+        let reference = self.reference?;
+        if self.count < 2 || self.span() < MIN_SWEEP_SPAN {
+            return None;
+        }
+        let n = self.count as f32;
+        let denominator = n * self.sum_tt - self.sum_t * self.sum_t;
+        let axis = |i: usize| {
+            let slope = (n * self.sum_tb[i] - self.sum_t * self.sum_b[i]) / denominator;
+            let offset = (self.sum_b[i] - slope * self.sum_t) / n - slope * reference;
+            (offset, slope)
+        };
+        let (x, y, z) = (axis(0), axis(1), axis(2));
+        Some(GyroTemperatureModel { offset: (x.0, y.0, z.0), slope: (x.1, y.1, z.1) })
+    }
+}
+
+/// Capture stage of `MPU6000::calibrate`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CalStage {
+    GyroBias,
+    Level,
+}
+
+/// Reported to `CalibrationPlan::progress` while `MPU6000::calibrate` runs
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CalProgress {
+    /// `captured` of `total` readings of `stage` taken
+    Sample {
+        stage: CalStage,
+        captured: u16,
+        total: u16,
+    },
+    /// Motion was detected and `stage` started over, `restarts` times so far
+    Restarted {
+        stage: CalStage,
+        restarts: u8,
+    },
+    Complete,
+}
+
+/// What `MPU6000::calibrate` measures and where the results go
+#[derive(Copy, Clone, Debug)]
+pub struct CalibrationPlan {
+    /// Readings averaged per capture
+    pub samples: u16,
+    /// Also capture the mounting tilt into the level trim, needs a flat surface
+    pub level: bool,
+    /// Write the gyro bias to the offset registers instead of `gyro_bias`
+    pub hardware_offsets: bool,
+    /// Rate in °/s any axis may drift from the first reading of a capture before it
+    /// counts as motion and the capture restarts, `None` accepts any reading
+    pub motion_threshold: Option<f32>,
+    /// Restarts after which `calibrate` gives up with `Error::Motion`
+    pub max_restarts: u8,
+    /// Called for every reading, restart and on completion, e.g. to drive an LED
+    pub progress: Option<fn(CalProgress)>,
+}
+
+impl Default for CalibrationPlan {
+    fn default() -> Self {
+        Self {
+            samples: 500,
+            level: false,
+            hardware_offsets: false,
+            motion_threshold: Some(2.0),
+            max_restarts: 10,
+            progress: None,
+        }
+    }
+}
+
+impl CalibrationPlan {
+    pub(crate) fn report(&self, progress: CalProgress) {
+        if let Some(callback) = self.progress {
+            callback(progress)
+        }
+    }
+}
+
+/// LSB per °/s of the gyro offset registers, which use the +/- 1000°/s sensitivity
+/// whatever range is selected
+pub const GYRO_OFFSET_SENSITIVITY: f32 = 32.8;
+/// LSB per g of the accelerometer offset registers read as 16 bit values, which use
+/// the +/- 16g sensitivity. Bit 0 is reserved and must be preserved.
+pub const ACCELEROMETER_OFFSET_SENSITIVITY: f32 = 2048.0;
+
+fn to_register(value: f32) -> i16 {
+    libm::roundf(value).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+/// Gyro offset register values cancelling a zero rate `bias` in °/s, e.g.
+/// `CalibrationData::gyro_bias`
+pub fn gyro_offset_counts(bias: (f32, f32, f32)) -> [i16; 3] {
+    let counts = |bias: f32| to_register(-bias * GYRO_OFFSET_SENSITIVITY);
+    [counts(bias.0), counts(bias.1), counts(bias.2)]
+}
+
+/// Accelerometer offset register values cancelling `bias_mg` in mg, given the values
+/// read from the registers, which hold the factory trim after reset. Bit 0 of each
+/// value is kept.
+pub fn accelerometer_offset_counts(current: [i16; 3], bias_mg: (f32, f32, f32)) -> [i16; 3] {
+    let counts = |current: i16, bias: f32| {
+        let steps = to_register(bias * ACCELEROMETER_OFFSET_SENSITIVITY / 2000.0);
+        current.saturating_sub(steps.saturating_mul(2)) & !1 | current & 1
+    };
+    [counts(current[0], bias_mg.0), counts(current[1], bias_mg.1), counts(current[2], bias_mg.2)]
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_offset_counts() {
+        use super::{accelerometer_offset_counts, gyro_offset_counts};
+
+        assert_eq!(gyro_offset_counts((1.0, -0.5, 0.0)), [-33, 16, 0]);
+        assert_eq!(gyro_offset_counts((2000.0, 0.0, 0.0))[0], i16::MIN);
+        let trim = [-2001, 1200, 0];
+        assert_eq!(accelerometer_offset_counts(trim, (0.0, 0.0, 0.0)), trim);
+        assert_eq!(accelerometer_offset_counts(trim, (10.0, -20.0, 1000.0)), [-2021, 1240, -2048]);
+    }
+
+    #[test]
+    fn test_temperature_sweep() {
+        let rates = |celsius: f32| (0.5 + 0.1 * (celsius - 30.0), -0.02 * celsius, 1.0);
+        let mut sweep = super::TemperatureSweep::new();
+        sweep.update(30.0, rates(30.0));
+        sweep.update(30.5, rates(30.5));
+        assert_eq!(sweep.fit(), None);
+        for i in 2..=20 {
+            let celsius = 30.0 + i as f32 * 0.5;
+            sweep.update(celsius, rates(celsius));
+        }
+        let model = sweep.fit().unwrap();
+        assert!((model.slope.0 - 0.1).abs() < 1e-3 && (model.slope.1 + 0.02).abs() < 1e-3);
+        let bias = model.bias(35.0);
+        assert!((bias.0 - 1.0).abs() < 1e-3 && (bias.1 + 0.7).abs() < 1e-3);
+        assert!((bias.2 - 1.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,100 @@
+//! Driver configuration that can be captured and re-applied as a whole
+
+use fugit::MillisDurationU32;
+
+use crate::auto_range::AutoRange;
+use crate::calibration::CalibrationData;
+use crate::health::TemperatureMonitor;
+use crate::measurement::ClipStats;
+use crate::orientation::Orientation;
+use crate::registers::{AccelerometerRange, GyroBandwidth, GyroRange, ProductId};
+use crate::variant::{Variant, WhoAmISet};
+use crate::FifoEnable;
+
+/// Configuration tracked by the driver, see `MPU6000::config` and `MPU6000::configure`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mpu6000Config {
+    pub accelerometer_range: AccelerometerRange,
+    pub gyro_range: GyroRange,
+    /// DLPF_CFG value, see `MPU6000::set_dlpf`
+    pub dlpf: u8,
+    pub sample_rate_divider: u8,
+}
+
+/// Power-on register values
+impl Default for Mpu6000Config {
+    fn default() -> Self {
+        Self {
+            accelerometer_range: AccelerometerRange::G2,
+            gyro_range: GyroRange::DPS250,
+            dlpf: 0,
+            sample_rate_divider: 0,
+        }
+    }
+}
+
+/// Waits after each step of `MPU6000::reset_with_timing`. The defaults are
+/// conservative, many boards are ready much sooner.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ResetTiming {
+    /// After DEVICE_RESET
+    pub device_reset: MillisDurationU32,
+    /// After the gyro, accelerometer and temperature signal path reset
+    pub signal_path_reset: MillisDurationU32,
+}
+
+impl Default for ResetTiming {
+    fn default() -> Self {
+        Self {
+            device_reset: MillisDurationU32::millis(150),
+            signal_path_reset: MillisDurationU32::millis(150),
+        }
+    }
+}
+
+/// Driver side state, to rebuild a driver with `MPU6000::from_state` after `free`
+/// without touching the device. Software gyro filters are not included and restart.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DriverState {
+    pub config: Mpu6000Config,
+    pub variant: Variant,
+    pub gyro_bandwidth: GyroBandwidth,
+    pub fifo_enable: FifoEnable,
+    pub orientation: Orientation,
+    pub accepted_whoami: WhoAmISet,
+    pub product_id: ProductId,
+    pub calibration: CalibrationData,
+    pub auto_range: Option<AutoRange>,
+    pub clip_stats: ClipStats,
+    pub temperature_monitor: Option<TemperatureMonitor>,
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    #[test]
+    fn test_postcard_round_trip() {
+        use super::Mpu6000Config;
+        use crate::attitude::LevelTrim;
+        use crate::calibration::CalibrationData;
+        use crate::registers::{AccelerometerRange, GyroRange};
+
+        let config = Mpu6000Config {
+            accelerometer_range: AccelerometerRange::G16,
+            gyro_range: GyroRange::DPS2000,
+            dlpf: 3,
+            sample_rate_divider: 7,
+        };
+        let mut buffer = [0u8; 32];
+        let bytes = postcard::to_slice(&config, &mut buffer).unwrap();
+        assert_eq!(postcard::from_bytes::<Mpu6000Config>(bytes).unwrap(), config);
+
+        let calibration = CalibrationData {
+            level_trim: Some(LevelTrim { roll: 1.5, pitch: -2.0 }),
+            gyro_bias: Some((0.25, -0.5, 0.0)),
+            gyro_temperature_model: None,
+        };
+        let bytes = postcard::to_slice(&calibration, &mut buffer).unwrap();
+        assert_eq!(postcard::from_bytes::<CalibrationData>(bytes).unwrap(), calibration);
+    }
+}
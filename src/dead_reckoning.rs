@@ -0,0 +1,134 @@
+//! Experimental velocity and position from linear acceleration
+//!
+//! Double integration of accelerometer noise and bias drifts quickly, meters within
+//! seconds. Zero-velocity updates bound the drift only for motions that regularly
+//! come to rest, e.g. steps or short pick-and-place moves.
+
+use fugit::Duration;
+
+use crate::stats::Stats;
+use crate::units::STANDARD_GRAVITY;
+
+type Vector = (f32, f32, f32);
+
+/// Mean linear acceleration still counted as rest, in standard deviations of the
+/// variance threshold
+const REST_DEVIATIONS: f32 = 3.0;
+
+/// Integrates earth frame linear acceleration, see `attitude::linear_acceleration`,
+/// into velocity and position. Velocity is reset to zero whenever the last `N`
+/// samples show the device at rest: low variance and a mean near zero, so a steady
+/// push with constant acceleration keeps integrating.
+#[derive(Copy, Clone, Debug)]
+pub struct DeadReckoner<const N: usize> {
+    window: Stats<N>,
+    max_variance: f32,
+    velocity: Vector,
+    position: Vector,
+    zero_velocity_updates: u32,
+}
+
+impl<const N: usize> DeadReckoner<N> {
+    /// `max_variance` in g² per axis, below which the device counts as at rest if the
+    /// mean of every axis is also within three standard deviations of zero
+    pub fn new(max_variance: f32) -> Self {
+        Self {
+            window: Stats::new(),
+            max_variance,
+            velocity: (0.0, 0.0, 0.0),
+            position: (0.0, 0.0, 0.0),
+            zero_velocity_updates: 0,
+        }
+    }
+
+    /// Integrates one linear acceleration sample in g taken `dt` seconds after the
+    /// previous one, returns the velocity in m/s
+    pub fn update_dt(&mut self, acceleration: Vector, dt: f32) -> Vector {
+        self.window.update(acceleration);
+        if self.is_stationary() {
+            self.velocity = (0.0, 0.0, 0.0);
+            self.zero_velocity_updates = self.zero_velocity_updates.saturating_add(1);
+            return self.velocity;
+        }
+        let (vx, vy, vz) = self.velocity;
+        let scale = STANDARD_GRAVITY * dt;
+        self.velocity =
+            (vx + acceleration.0 * scale, vy + acceleration.1 * scale, vz + acceleration.2 * scale);
+        let (px, py, pz) = self.position;
+        let (vx, vy, vz) = self.velocity;
+        self.position = (px + vx * dt, py + vy * dt, pz + vz * dt);
+        self.velocity
+    }
+
+    /// Integrates one sample taken `dt` after the previous one
+    pub fn update<const NOM: u32, const DENOM: u32>(
+        &mut self,
+        acceleration: Vector,
+        dt: Duration<u32, NOM, DENOM>,
+    ) -> Vector {
+        self.update_dt(acceleration, dt.to_micros() as f32 / 1_000_000.0)
+    }
+
+    /// m/s
+    pub fn velocity(&self) -> Vector {
+        self.velocity
+    }
+
+    /// m from the start or the last `reset`
+    pub fn position(&self) -> Vector {
+        self.position
+    }
+
+    pub fn is_stationary(&self) -> bool {
+        let limit = REST_DEVIATIONS * libm::sqrtf(self.max_variance);
+        let near_zero = self
+            .window
+            .mean()
+            .is_some_and(|(x, y, z)| x.abs() < limit && y.abs() < limit && z.abs() < limit);
+        near_zero && self.window.is_still(self.max_variance)
+    }
+
+    /// Number of samples velocity was clamped to zero
+    pub fn zero_velocity_updates(&self) -> u32 {
+        self.zero_velocity_updates
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.max_variance)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_dead_reckoner() {
+        use super::DeadReckoner;
+
+        let mut reckoner = DeadReckoner::<4>::new(1e-4);
+        // 0.1g along x for 1s at 100Hz, alternating so the window never looks still
+        for i in 0..100 {
+            reckoner.update_dt((if i % 2 == 0 { 0.05 } else { 0.15 }, 0.0, 0.0), 0.01);
+        }
+        let (vx, vy, _) = reckoner.velocity();
+        assert!((vx - 0.980_665).abs() < 1e-3 && vy == 0.0);
+        assert!(reckoner.position().0 > 0.4);
+        for _ in 0..4 {
+            reckoner.update_dt((0.0, 0.0, 0.0), 0.01);
+        }
+        assert_eq!(reckoner.velocity(), (0.0, 0.0, 0.0));
+        assert!(reckoner.is_stationary() && reckoner.zero_velocity_updates() == 1);
+    }
+
+    #[test]
+    fn test_constant_push() {
+        use super::DeadReckoner;
+
+        let mut reckoner = DeadReckoner::<4>::new(1e-4);
+        // steady 0.1g along x for 1s at 100Hz, no variance at all
+        for _ in 0..100 {
+            reckoner.update_dt((0.1, 0.0, 0.0), 0.01);
+        }
+        assert!((reckoner.velocity().0 - 0.980_665).abs() < 1e-3);
+        assert!(!reckoner.is_stationary() && reckoner.zero_velocity_updates() == 0);
+    }
+}
@@ -0,0 +1,168 @@
+//! Gyro bias tracking and re-zeroing during stationary periods
+
+use fugit::MillisDurationU32;
+
+use crate::bus::RegAccess;
+use crate::profile::Profile;
+use crate::stats::Stats;
+use crate::MPU6000;
+
+type Vector = (f32, f32, f32);
+
+/// Veto callback for `AutoRecalibration`, given the bias about to be applied
+pub type AllowRecalibration = fn(Vector) -> bool;
+
+/// Exponentially weighted per-axis gyro bias estimate, updated only while the
+/// variance over the last `N` samples shows the device at rest
+#[derive(Copy, Clone, Debug)]
+pub struct GyroDriftEstimator<const N: usize> {
+    window: Stats<N>,
+    weight: f32,
+    max_variance: f32,
+    bias: Vector,
+    confidence: f32,
+}
+
+impl<const N: usize> GyroDriftEstimator<N> {
+    /// `weight` in (0, 1] of each stationary window mean in the estimate,
+    /// `max_variance` in (°/s)² per axis below which the device counts as still
+    pub fn new(weight: f32, max_variance: f32) -> Self {
+        Self {
+            window: Stats::new(),
+            weight: weight.clamp(0.0, 1.0),
+            max_variance,
+            bias: (0.0, 0.0, 0.0),
+            confidence: 0.0,
+        }
+    }
+
+    /// Feeds one rate sample in °/s without bias correction, e.g. from
+    /// `Gyro::normalize`. Returns whether the estimate was updated.
+    pub fn update(&mut self, rates: Vector) -> bool {
+        self.window.update(rates);
+        if !self.window.is_still(self.max_variance) {
+            return false;
+        }
+        let (x, y, z) = match self.window.mean() {
+            Some(mean) => mean,
+            None => return false,
+        };
+        let weight = self.weight;
+        let (bx, by, bz) = self.bias;
+        self.bias = (bx + weight * (x - bx), by + weight * (y - by), bz + weight * (z - bz));
+        self.confidence += weight * (1.0 - self.confidence);
+        true
+    }
+
+    /// Current estimate in °/s
+    pub fn bias(&self) -> Vector {
+        self.bias
+    }
+
+    /// Share of the estimate backed by stationary data, from 0 before the first
+    /// stationary window towards 1
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+
+    /// Hands the estimate to the driver's software bias correction
+    pub fn apply_to<BUS: RegAccess, P: Profile>(&self, mpu6000: &mut MPU6000<BUS, P>) {
+        mpu6000.set_gyro_bias(Some(self.bias));
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new(self.weight, self.max_variance)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct RecalibrationConfig {
+    /// How long the device must stay still before re-zeroing
+    pub hold_time: MillisDurationU32,
+    /// Window variance in (°/s)² per axis below which the device becomes still
+    pub enter_variance: f32,
+    /// Window variance above which it stops being still, at least `enter_variance`
+    pub exit_variance: f32,
+    /// Returning false vetoes the re-zero, e.g. while armed
+    pub allow: Option<AllowRecalibration>,
+}
+
+/// Opt-in automatic gyro re-zeroing: once per stationary period lasting `hold_time`,
+/// the mean rate over that period becomes the driver's gyro bias
+#[derive(Copy, Clone, Debug)]
+pub struct AutoRecalibration<const N: usize> {
+    config: RecalibrationConfig,
+    window: Stats<N>,
+    still: bool,
+    done: bool,
+    still_us: u32,
+    sum: [f32; 3],
+    count: u32,
+}
+
+impl<const N: usize> AutoRecalibration<N> {
+    pub fn new(config: RecalibrationConfig) -> Self {
+        Self {
+            config,
+            window: Stats::new(),
+            still: false,
+            done: false,
+            still_us: 0,
+            sum: [0.0; 3],
+            count: 0,
+        }
+    }
+
+    pub fn is_still(&self) -> bool {
+        self.still
+    }
+
+    /// Feeds one rate sample in °/s without bias correction, taken at the device
+    /// sample rate. Returns the bias if it was applied.
+    pub fn update<BUS: RegAccess, P: Profile>(
+        &mut self,
+        mpu6000: &mut MPU6000<BUS, P>,
+        rates: Vector,
+    ) -> Option<Vector> {
+        self.window.update(rates);
+        let variance = self.window.variance()?;
+        let below = |limit: f32| variance.0 < limit && variance.1 < limit && variance.2 < limit;
+        let still = if self.still {
+            below(self.config.exit_variance.max(self.config.enter_variance))
+        } else {
+            self.window.is_full() && below(self.config.enter_variance)
+        };
+        if !still {
+            self.still = false;
+            self.done = false;
+            self.still_us = 0;
+            self.sum = [0.0; 3];
+            self.count = 0;
+            return None;
+        }
+        self.still = true;
+        if self.done {
+            return None;
+        }
+        self.sum[0] += rates.0;
+        self.sum[1] += rates.1;
+        self.sum[2] += rates.2;
+        self.count = self.count.saturating_add(1);
+        self.still_us = self.still_us.saturating_add(1_000_000 / mpu6000.sample_rate() as u32);
+        if self.still_us < self.config.hold_time.to_micros() {
+            return None;
+        }
+        self.done = true;
+        let n = self.count as f32;
+        let bias = (self.sum[0] / n, self.sum[1] / n, self.sum[2] / n);
+        if let Some(allow) = self.config.allow {
+            if !allow(bias) {
+                debug!("gyro re-zero vetoed");
+                return None;
+            }
+        }
+        debug!("gyro re-zeroed to {} {} {}", bias.0, bias.1, bias.2);
+        mpu6000.set_gyro_bias(Some(bias));
+        Some(bias)
+    }
+}
@@ -0,0 +1,111 @@
+//! Bus free register field packing and rate arithmetic, shared by the driver and
+//! usable on the host for tooling and exhaustive testing
+
+/// Mask of a `len` bit field at `offset`, bits beyond the register are dropped
+pub fn field_mask(offset: u8, len: u8) -> u8 {
+    let bits = ((1u16 << len.min(8)) - 1) as u8;
+    bits.checked_shl(offset as u32).unwrap_or(0)
+}
+
+/// `value` with the field at `offset` replaced by the low `len` bits of `bits`
+pub fn set_field(value: u8, offset: u8, len: u8, bits: u8) -> u8 {
+    let mask = field_mask(offset, len);
+    value & !mask | bits.checked_shl(offset as u32).unwrap_or(0) & mask
+}
+
+/// The `len` bit field at `offset` of `value`
+pub fn get_field(value: u8, offset: u8, len: u8) -> u8 {
+    (value & field_mask(offset, len)).checked_shr(offset as u32).unwrap_or(0)
+}
+
+/// Gyro output rate in Hz for DLPF_CFG, 8kHz with the DLPF off
+pub fn gyro_output_rate(dlpf: u8) -> u16 {
+    if 0 < dlpf && dlpf < 7 {
+        1_000
+    } else {
+        8_000
+    }
+}
+
+/// Output data rate in Hz for DLPF_CFG and SMPLRT_DIV
+pub fn sample_rate(dlpf: u8, divider: u8) -> u16 {
+    gyro_output_rate(dlpf) / (divider as u16 + 1)
+}
+
+/// SMPLRT_DIV for `rate` Hz, `None` if the divider cannot reach it
+pub fn sample_rate_divider(dlpf: u8, rate: u16) -> Option<u8> {
+    match gyro_output_rate(dlpf).checked_div(rate) {
+        Some(quotient) if (1..=256).contains(&quotient) => Some((quotient - 1) as u8),
+        _ => None,
+    }
+}
+
+/// Three big endian i16 as laid out in the sensor data registers
+pub fn decode_vector(bytes: [u8; 6]) -> [i16; 3] {
+    [
+        i16::from_be_bytes([bytes[0], bytes[1]]),
+        i16::from_be_bytes([bytes[2], bytes[3]]),
+        i16::from_be_bytes([bytes[4], bytes[5]]),
+    ]
+}
+
+pub fn encode_vector(vector: [i16; 3]) -> [u8; 6] {
+    let [x, y, z] = [vector[0].to_be_bytes(), vector[1].to_be_bytes(), vector[2].to_be_bytes()];
+    [x[0], x[1], y[0], y[1], z[0], z[1]]
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_field_round_trip() {
+        use super::{field_mask, get_field, set_field};
+
+        for offset in 0..8 {
+            for len in 0..=8 {
+                let mask = field_mask(offset, len);
+                for value in 0..=255u8 {
+                    for bits in 0..=255u8 {
+                        let packed = set_field(value, offset, len, bits);
+                        assert_eq!(packed & !mask, value & !mask);
+                        assert_eq!(
+                            get_field(packed, offset, len),
+                            get_field(bits << offset, offset, len)
+                        );
+                        assert_eq!(
+                            set_field(packed, offset, len, get_field(value, offset, len)),
+                            value
+                        );
+                    }
+                }
+            }
+        }
+        assert_eq!(field_mask(3, 2), 0b1_1000);
+        assert_eq!(field_mask(7, 8), 0x80);
+        assert_eq!(set_field(0, 9, 1, 1), 0);
+    }
+
+    #[test]
+    fn test_sample_rate_divider() {
+        use super::{gyro_output_rate, sample_rate, sample_rate_divider};
+
+        for dlpf in 0..8 {
+            // exact divisions map back to the same divider
+            for divider in (0..=255u8).filter(|&d| gyro_output_rate(dlpf) % (d as u16 + 1) == 0) {
+                let rate = sample_rate(dlpf, divider);
+                assert_eq!(sample_rate_divider(dlpf, rate), Some(divider));
+            }
+        }
+        assert_eq!(sample_rate_divider(1, 0), None);
+        assert_eq!(sample_rate_divider(1, 2000), None);
+    }
+
+    #[test]
+    fn test_vector_round_trip() {
+        use super::{decode_vector, encode_vector};
+
+        for value in (i16::MIN..=i16::MAX).step_by(7) {
+            let vector = [value, value.wrapping_neg(), value ^ 0x5555];
+            assert_eq!(decode_vector(encode_vector(vector)), vector);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+/// Errors of operations that can fail for reasons other than the bus
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error<E> {
+    Bus(E),
+    /// Not available on the configured `Variant`
+    Unsupported,
+    /// The awaited condition was not met in time
+    Timeout,
+    /// Argument outside the range the device supports
+    InvalidArgument,
+    /// The selected external clock does not drive the device
+    ClockNotPresent,
+    /// The device kept moving during a capture that needs it at rest
+    Motion,
+}
+
+#[cfg(feature = "ufmt")]
+impl<E: ufmt::uDebug> ufmt::uDebug for Error<E> {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        match self {
+            Self::Bus(e) => f.debug_tuple("Bus")?.field(e)?.finish(),
+            Self::Unsupported => f.write_str("Unsupported"),
+            Self::Timeout => f.write_str("Timeout"),
+            Self::InvalidArgument => f.write_str("InvalidArgument"),
+            Self::ClockNotPresent => f.write_str("ClockNotPresent"),
+            Self::Motion => f.write_str("Motion"),
+        }
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<E: ufmt::uDebug> ufmt::uDisplay for Error<E> {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        match self {
+            Self::Bus(e) => ufmt::uwrite!(f, "bus error {:?}", e),
+            Self::Unsupported => f.write_str("not supported by the device variant"),
+            Self::Timeout => f.write_str("timed out"),
+            Self::InvalidArgument => f.write_str("argument out of range"),
+            Self::ClockNotPresent => f.write_str("external clock not present"),
+            Self::Motion => f.write_str("device not at rest"),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "ufmt"))]
+mod test {
+    extern crate std;
+
+    use std::string::String;
+
+    struct Output(String);
+
+    impl ufmt::uWrite for Output {
+        type Error = core::convert::Infallible;
+
+        fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+            self.0.push_str(s);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_ufmt() {
+        use super::Error;
+
+        let mut output = Output(String::new());
+        ufmt::uwrite!(output, "{:?} {:?}", Error::Bus(5u8), Error::<u8>::Timeout).ok();
+        assert_eq!(output.0, "Bus(5) Timeout");
+        let mut output = Output(String::new());
+        ufmt::uwrite!(output, "{}", Error::Bus(5u8)).ok();
+        assert_eq!(output.0, "bus error 5");
+    }
+}
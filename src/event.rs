@@ -0,0 +1,137 @@
+//! Bounded queue of sensor events, filled in interrupt context and drained by the main loop
+//!
+//! The queue itself is not synchronized, share it between the ISR and the main
+//! loop through a critical section mutex.
+
+use crate::interrupt::InterruptStatus;
+use crate::variant::Variant;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ImuEvent {
+    DataReady,
+    Motion,
+    FifoOverflow,
+    I2cMaster,
+    /// DMP packet ready, see `Interrupt::Dmp`
+    Dmp,
+    Fsync,
+    /// The device reverted to its power-on state on its own, e.g. after a brownout,
+    /// and needs to be configured again
+    UnexpectedReset,
+    /// Sensor data stopped changing, see `MPU6000::set_lock_up_detection`. Reset and
+    /// configure the device again.
+    LockUp,
+}
+
+/// Ring buffer of up to `N` events, newest events are dropped when full
+#[derive(Copy, Clone, Debug)]
+pub struct EventQueue<const N: usize> {
+    events: [ImuEvent; N],
+    head: usize,
+    len: usize,
+    dropped: u32,
+}
+
+impl<const N: usize> Default for EventQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> EventQueue<N> {
+    pub const fn new() -> Self {
+        Self { events: [ImuEvent::DataReady; N], head: 0, len: 0, dropped: 0 }
+    }
+
+    /// Returns the event back if the queue is full
+    pub fn push(&mut self, event: ImuEvent) -> Result<(), ImuEvent> {
+        if self.len == N {
+            self.dropped = self.dropped.saturating_add(1);
+            return Err(event);
+        }
+        self.events[(self.head + self.len) % N] = event;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Pushes one event per pending source in `status`, returns how many were dropped.
+    /// Bit 3 is reported as `Fsync` on the MPU6500 class and as `I2cMaster` otherwise.
+    pub fn push_status(&mut self, status: InterruptStatus, variant: Variant) -> usize {
+        let fsync = status.fsync(variant);
+        let events = [
+            (status.data_ready(), ImuEvent::DataReady),
+            (status.motion(), ImuEvent::Motion),
+            (status.fifo_overflow(), ImuEvent::FifoOverflow),
+            (fsync.is_none() && status.i2c_master(), ImuEvent::I2cMaster),
+            (status.dmp(), ImuEvent::Dmp),
+            (fsync == Some(true), ImuEvent::Fsync),
+        ];
+        let mut dropped = 0;
+        for &(pending, event) in events.iter() {
+            if pending && self.push(event).is_err() {
+                dropped += 1;
+            }
+        }
+        dropped
+    }
+
+    pub fn pop(&mut self) -> Option<ImuEvent> {
+        if self.len == 0 {
+            return None;
+        }
+        let event = self.events[self.head];
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(event)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Events rejected because the queue was full since creation
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_event_queue() {
+        use super::{EventQueue, ImuEvent};
+        use crate::interrupt::InterruptStatus;
+        use crate::variant::Variant;
+
+        let mut queue = EventQueue::<2>::new();
+        assert_eq!(queue.push_status(InterruptStatus(0b0101_0001), Variant::Mpu6000), 1);
+        assert_eq!(queue.push(ImuEvent::Fsync), Err(ImuEvent::Fsync));
+        assert_eq!(queue.dropped(), 2);
+        assert_eq!(queue.pop(), Some(ImuEvent::DataReady));
+        queue.push(ImuEvent::Fsync).unwrap();
+        assert_eq!(queue.pop(), Some(ImuEvent::Motion));
+        assert_eq!(queue.pop(), Some(ImuEvent::Fsync));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn test_push_status_fsync() {
+        use super::{EventQueue, ImuEvent};
+        use crate::interrupt::InterruptStatus;
+        use crate::variant::Variant;
+
+        let mut queue = EventQueue::<4>::new();
+        assert_eq!(queue.push_status(InterruptStatus(0b0000_1001), Variant::Mpu6500), 0);
+        assert_eq!(queue.pop(), Some(ImuEvent::DataReady));
+        assert_eq!(queue.pop(), Some(ImuEvent::Fsync));
+        assert_eq!(queue.pop(), None);
+        queue.push_status(InterruptStatus(0b0000_1000), Variant::Mpu6050);
+        assert_eq!(queue.pop(), Some(ImuEvent::I2cMaster));
+        assert_eq!(queue.pop(), None);
+    }
+}
@@ -0,0 +1,274 @@
+//! Decoding of drained FIFO contents
+//!
+//! Each frame holds the enabled sensors in register order: accelerometer X/Y/Z,
+//! temperature, then the enabled gyro axes, all big endian i16. Slave sensor data is
+//! not decoded, frames are only well defined with the slaves disabled.
+
+use crate::measurement::{Acceleration, Gyro, Temperature};
+use crate::orientation::Orientation;
+use crate::FifoEnable;
+
+/// Microseconds on the caller's clock
+pub type Timestamp = fugit::TimerInstantU64<1_000_000>;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FifoFrame {
+    pub acceleration: Option<Acceleration>,
+    pub temperature: Option<Temperature>,
+    /// Set if any gyro axis is enabled, disabled axes read 0
+    pub gyro: Option<Gyro>,
+    /// Synthesized sample time, see `FifoFrames::with_timestamps`
+    pub timestamp: Option<Timestamp>,
+}
+
+/// Divided delivery rates for accelerometer and gyro, e.g. full rate gyro for the
+/// control loop and every 4th accelerometer sample for the estimator.
+/// Divisors of 0 are treated as 1, the phase carries over between drains.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SensorDecimation {
+    accelerometer: u16,
+    gyro: u16,
+    index: u32,
+}
+
+impl Default for SensorDecimation {
+    fn default() -> Self {
+        Self::new(1, 1)
+    }
+}
+
+impl SensorDecimation {
+    pub fn new(accelerometer: u16, gyro: u16) -> Self {
+        Self { accelerometer: accelerometer.max(1), gyro: gyro.max(1), index: 0 }
+    }
+
+    pub fn accelerometer(&self) -> u16 {
+        self.accelerometer
+    }
+
+    pub fn gyro(&self) -> u16 {
+        self.gyro
+    }
+
+    /// Advances by one sample, returns whether accelerometer and gyro are delivered
+    pub fn next_sample(&mut self) -> (bool, bool) {
+        let index = self.index;
+        // wrap on a common multiple so the phase of both stays continuous
+        let period = self.accelerometer as u32 * self.gyro as u32;
+        self.index = (index + 1) % period;
+        (index % self.accelerometer as u32 == 0, index % self.gyro as u32 == 0)
+    }
+
+    /// Drops the sensors not due for delivery from `frame`
+    pub fn apply(&mut self, frame: FifoFrame) -> FifoFrame {
+        let (acceleration, gyro) = self.next_sample();
+        FifoFrame {
+            acceleration: frame.acceleration.filter(|_| acceleration),
+            gyro: frame.gyro.filter(|_| gyro),
+            ..frame
+        }
+    }
+
+    /// Restarts so the next sample delivers both sensors
+    pub fn reset(&mut self) {
+        self.index = 0
+    }
+}
+
+/// Bytes per frame for `config`, slaves excluded
+pub fn frame_size(config: &FifoEnable) -> usize {
+    let gyro_axes =
+        config.x_g_force as usize + config.y_g_force as usize + config.z_g_force as usize;
+    config.acceleration as usize * 6 + config.temperature as usize * 2 + gyro_axes * 2
+}
+
+/// Decodes the frame at the start of `bytes` in sensor axes without a timestamp,
+/// `None` if `bytes` is shorter than a frame or no sensor is enabled
+pub fn decode_frame(config: &FifoEnable, bytes: &[u8]) -> Option<FifoFrame> {
+    let size = frame_size(config);
+    if size == 0 || bytes.len() < size {
+        return None;
+    }
+    let mut words =
+        bytes[..size].chunks_exact(2).map(|word| i16::from_be_bytes([word[0], word[1]]));
+    let mut next = |enabled: bool| if enabled { words.next().unwrap_or(0) } else { 0 };
+    let acceleration = if config.acceleration {
+        let (x, y) = (next(true), next(true));
+        Some(Acceleration(x, y, next(true)))
+    } else {
+        None
+    };
+    let temperature = if config.temperature { Some(Temperature(next(true))) } else { None };
+    let (x, y, z) = (next(config.x_g_force), next(config.y_g_force), next(config.z_g_force));
+    let gyro = if config.x_g_force || config.y_g_force || config.z_g_force {
+        Some(Gyro(x, y, z))
+    } else {
+        None
+    };
+    Some(FifoFrame { acceleration, temperature, gyro, timestamp: None })
+}
+
+/// Lazily decodes whole frames from drained FIFO bytes, trailing partial frames are ignored
+pub struct FifoFrames<'a> {
+    config: FifoEnable,
+    bytes: &'a [u8],
+    /// Time of the next frame and the sample period, in microseconds
+    timing: Option<(u64, u64)>,
+    orientation: Orientation,
+    decimation: Option<SensorDecimation>,
+    temperature: Option<Temperature>,
+}
+
+impl<'a> FifoFrames<'a> {
+    pub fn new(config: &FifoEnable, bytes: &'a [u8]) -> Self {
+        Self {
+            config: *config,
+            bytes,
+            timing: None,
+            orientation: Orientation::default(),
+            decimation: None,
+            temperature: None,
+        }
+    }
+
+    /// Timestamps frames assuming the last whole frame was sampled at `drain_time`
+    /// and the ones before it one sample period apart
+    pub fn with_timestamps(
+        config: &FifoEnable,
+        bytes: &'a [u8],
+        drain_time: Timestamp,
+        sample_rate: u16,
+    ) -> Self {
+        let mut frames = Self::new(config, bytes);
+        let period = 1_000_000 / sample_rate.max(1) as u64;
+        let earlier = frames.size_hint().0.saturating_sub(1) as u64 * period;
+        frames.timing = Some((drain_time.ticks().wrapping_sub(earlier), period));
+        frames
+    }
+
+    /// Maps accelerometer and gyro data to the board frame, see `MPU6000::set_orientation`
+    pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Leaves out accelerometer or gyro data not due under `decimation`, frames keep
+    /// their timestamps. Continue the next drain with `decimation()` to keep the phase.
+    pub fn with_decimation(mut self, decimation: SensorDecimation) -> Self {
+        self.decimation = Some(decimation);
+        self
+    }
+
+    /// Attaches `temperature` to frames without temperature data, e.g. a reading
+    /// taken out of band while the FIFO only carries motion data
+    pub fn with_temperature(mut self, temperature: Temperature) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Decimation state after the frames decoded so far
+    pub fn decimation(&self) -> Option<SensorDecimation> {
+        self.decimation
+    }
+}
+
+impl<'a> Iterator for FifoFrames<'a> {
+    type Item = FifoFrame;
+
+    fn next(&mut self) -> Option<FifoFrame> {
+        let frame = decode_frame(&self.config, self.bytes)?;
+        self.bytes = &self.bytes[frame_size(&self.config)..];
+        let timestamp = self.timing.as_mut().map(|(time, period)| {
+            let timestamp = Timestamp::from_ticks(*time);
+            *time = time.wrapping_add(*period);
+            timestamp
+        });
+        let frame = FifoFrame {
+            acceleration: frame.acceleration.map(|a| self.orientation.acceleration(a)),
+            gyro: frame.gyro.map(|g| self.orientation.gyro(g)),
+            temperature: frame.temperature.or(self.temperature),
+            timestamp,
+        };
+        Some(match &mut self.decimation {
+            Some(decimation) => decimation.apply(frame),
+            None => frame,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.bytes.len().checked_div(frame_size(&self.config)).unwrap_or(0);
+        (count, Some(count))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_fifo_frames() {
+        use super::{FifoFrame, FifoFrames, SensorDecimation, Timestamp};
+        use crate::measurement::{Acceleration, Gyro};
+        use crate::FifoEnable;
+
+        let config = FifoEnable {
+            acceleration: true,
+            x_g_force: true,
+            z_g_force: true,
+            ..Default::default()
+        };
+        let bytes = [0, 1, 0, 2, 0xff, 0xfe, 0, 3, 0, 4, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 7];
+        let mut frames = FifoFrames::new(&config, &bytes);
+        assert_eq!(frames.size_hint(), (2, Some(2)));
+        let frame = FifoFrame {
+            acceleration: Some(Acceleration(1, 2, -2)),
+            temperature: None,
+            gyro: Some(Gyro(3, 0, 4)),
+            timestamp: None,
+        };
+        assert_eq!(frames.next(), Some(frame));
+        assert_eq!(frames.next().unwrap().acceleration, Some(Acceleration(i16::MIN, 0, 0)));
+        assert_eq!(frames.next(), None);
+        let temperature = crate::measurement::Temperature(-521);
+        let mut frames = FifoFrames::new(&config, &bytes).with_temperature(temperature);
+        assert_eq!(frames.next().unwrap().temperature, Some(temperature));
+
+        let drain_time = Timestamp::from_ticks(10_000);
+        let frames = FifoFrames::with_timestamps(&config, &bytes, drain_time, 1000);
+        let mut timestamps = frames.map(|frame| frame.timestamp.map(|t| t.ticks()));
+        assert_eq!(timestamps.next(), Some(Some(9_000)));
+        assert_eq!(timestamps.next(), Some(Some(10_000)));
+
+        let decimation = SensorDecimation::new(2, 1);
+        let mut frames = FifoFrames::with_timestamps(&config, &bytes, drain_time, 1000)
+            .with_decimation(decimation);
+        assert!(frames.next().unwrap().acceleration.is_some());
+        let frame = frames.next().unwrap();
+        assert_eq!((frame.acceleration, frame.gyro), (None, Some(Gyro(0, 0, 0))));
+        assert_eq!(frame.timestamp, Some(drain_time));
+        let mut decimation = frames.decimation().unwrap();
+        assert_eq!(decimation.next_sample(), (true, true));
+    }
+
+    #[test]
+    fn test_decode_invariants() {
+        use super::{decode_frame, frame_size, FifoFrames};
+        use crate::FifoEnable;
+
+        let mut seed = 1u32;
+        let bytes: [u8; 97] = core::array::from_fn(|_| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) as u8
+        });
+        for bits in 0..=255u8 {
+            let config = FifoEnable::from(bits);
+            let size = frame_size(&config);
+            let count = bytes.len().checked_div(size).unwrap_or(0);
+            assert_eq!(FifoFrames::new(&config, &bytes).count(), count);
+            for (k, frame) in FifoFrames::new(&config, &bytes).enumerate() {
+                // decoding resumes at any frame boundary
+                assert_eq!(decode_frame(&config, &bytes[k * size..]), Some(frame));
+                assert_eq!(FifoFrames::new(&config, &bytes[k * size..]).next(), Some(frame));
+            }
+            assert_eq!(decode_frame(&config, &bytes[count * size..]), None);
+        }
+    }
+}
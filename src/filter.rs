@@ -0,0 +1,365 @@
+//! Allocation free software filters for accelerometer and gyro streams
+
+use fugit::Duration;
+
+/// -3dB cutoff in Hz of a first order lowpass with time constant `tau`
+pub fn cutoff_frequency<const NOM: u32, const DENOM: u32>(tau: Duration<u32, NOM, DENOM>) -> f32 {
+    1_000_000.0 / (2.0 * core::f32::consts::PI * tau.to_micros() as f32)
+}
+
+/// Single channel filter
+pub trait Filter {
+    fn update(&mut self, input: f32) -> f32;
+    fn reset(&mut self);
+}
+
+/// Applies an independent filter instance to each axis
+#[derive(Copy, Clone, Debug)]
+pub struct AxisFilter<F>(pub [F; 3]);
+
+impl<F: Clone> AxisFilter<F> {
+    pub fn new(filter: F) -> Self {
+        Self([filter.clone(), filter.clone(), filter])
+    }
+}
+
+impl<F: Filter> AxisFilter<F> {
+    pub fn update(&mut self, input: (f32, f32, f32)) -> (f32, f32, f32) {
+        (self.0[0].update(input.0), self.0[1].update(input.1), self.0[2].update(input.2))
+    }
+
+    pub fn reset(&mut self) {
+        self.0.iter_mut().for_each(|filter| filter.reset())
+    }
+}
+
+/// Mean of the last `N` inputs, averaging over fewer until the window fills up
+#[derive(Copy, Clone, Debug)]
+pub struct MovingAverage<const N: usize> {
+    window: [f32; N],
+    index: usize,
+    len: usize,
+}
+
+impl<const N: usize> MovingAverage<N> {
+    pub fn new() -> Self {
+        Self { window: [0.0; N], index: 0, len: 0 }
+    }
+}
+
+impl<const N: usize> Default for MovingAverage<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Filter for MovingAverage<N> {
+    fn update(&mut self, input: f32) -> f32 {
+        if N == 0 {
+            return input;
+        }
+        self.window[self.index] = input;
+        self.index = (self.index + 1) % N;
+        self.len = (self.len + 1).min(N);
+        self.window[..self.len].iter().sum::<f32>() / self.len as f32
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new()
+    }
+}
+
+/// Second order IIR section, transposed direct form II
+#[derive(Copy, Clone, Debug)]
+pub struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    z1: f32,
+    z2: f32,
+}
+
+impl Biquad {
+    /// Low-pass section, Q of 1/sqrt(2) gives a butterworth response
+    pub fn lowpass(cutoff: f32, sample_rate: f32, q: f32) -> Self {
+        let (sin, cos) = libm::sincosf(angular_frequency(cutoff, sample_rate));
+        let alpha = sin / (2.0 * q);
+        let b = (1.0 - cos) / 2.0;
+        Self::normalized([b, 1.0 - cos, b], [1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+    }
+
+    /// High-pass section, Q of 1/sqrt(2) gives a butterworth response
+    pub fn highpass(cutoff: f32, sample_rate: f32, q: f32) -> Self {
+        let (sin, cos) = libm::sincosf(angular_frequency(cutoff, sample_rate));
+        let alpha = sin / (2.0 * q);
+        let b = (1.0 + cos) / 2.0;
+        Self::normalized([b, -1.0 - cos, b], [1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+    }
+
+    /// Band-pass section with unity gain at `center` Hz, higher Q gives a narrower band
+    pub fn bandpass(center: f32, sample_rate: f32, q: f32) -> Self {
+        let (sin, cos) = libm::sincosf(angular_frequency(center, sample_rate));
+        let alpha = sin / (2.0 * q);
+        Self::normalized([alpha, 0.0, -alpha], [1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+    }
+
+    /// Band-stop section at `center` Hz, higher Q gives a narrower notch
+    pub fn notch(center: f32, sample_rate: f32, q: f32) -> Self {
+        let (sin, cos) = libm::sincosf(angular_frequency(center, sample_rate));
+        let alpha = sin / (2.0 * q);
+        Self::normalized([1.0, -2.0 * cos, 1.0], [1.0 + alpha, -2.0 * cos, 1.0 - alpha])
+    }
+
+    fn normalized(b: [f32; 3], a: [f32; 3]) -> Self {
+        let (b0, b1, b2) = (b[0] / a[0], b[1] / a[0], b[2] / a[0]);
+        Self { b0, b1, b2, a1: a[1] / a[0], a2: a[2] / a[0], z1: 0.0, z2: 0.0 }
+    }
+
+    /// Replaces coefficients while keeping filter state, for glitch free retuning
+    pub fn retune(&mut self, other: &Self) {
+        let (z1, z2) = (self.z1, self.z2);
+        *self = Self { z1, z2, ..*other };
+    }
+}
+
+impl Filter for Biquad {
+    fn update(&mut self, input: f32) -> f32 {
+        let output = self.b0 * input + self.z1;
+        self.z1 = self.b1 * input - self.a1 * output + self.z2;
+        self.z2 = self.b2 * input - self.a2 * output;
+        output
+    }
+
+    fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+}
+
+fn angular_frequency(frequency: f32, sample_rate: f32) -> f32 {
+    // keep below nyquist, coefficients are meaningless beyond it
+    let frequency = frequency.max(0.0).min(sample_rate * 0.49);
+    2.0 * core::f32::consts::PI * frequency / sample_rate
+}
+
+/// Butterworth low-pass made of one (2nd order) or two (4th order) biquad stages
+#[derive(Copy, Clone, Debug)]
+pub struct LowPass {
+    stages: [Biquad; 2],
+    num_stages: usize,
+}
+
+impl LowPass {
+    /// `stages` is clamped to 1..=2
+    pub fn new(cutoff: f32, sample_rate: f32, stages: usize) -> Self {
+        let num_stages = stages.clamp(1, 2);
+        Self { stages: Self::design(cutoff, sample_rate, num_stages), num_stages }
+    }
+
+    fn design(cutoff: f32, sample_rate: f32, num_stages: usize) -> [Biquad; 2] {
+        let qs = if num_stages == 1 { [0.707_106_77, 0.0] } else { [0.541_196_1, 1.306_563] };
+        [Biquad::lowpass(cutoff, sample_rate, qs[0]), Biquad::lowpass(cutoff, sample_rate, qs[1])]
+    }
+
+    /// Changes cutoff at runtime without resetting filter state
+    pub fn set_cutoff(&mut self, cutoff: f32, sample_rate: f32) {
+        let design = Self::design(cutoff, sample_rate, self.num_stages);
+        for (stage, design) in self.stages.iter_mut().zip(design.iter()) {
+            stage.retune(design);
+        }
+    }
+}
+
+impl Filter for LowPass {
+    fn update(&mut self, input: f32) -> f32 {
+        self.stages[..self.num_stages].iter_mut().fold(input, |value, stage| stage.update(value))
+    }
+
+    fn reset(&mut self) {
+        self.stages.iter_mut().for_each(|stage| stage.reset())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Decimation {
+    /// Keep the last of every `factor` samples, filter beforehand to avoid aliasing
+    Keep,
+    /// Output the mean of every `factor` samples
+    Average,
+}
+
+/// Reduces the sample rate by an integer factor, e.g. 8kHz gyro reads to 1kHz
+#[derive(Copy, Clone, Debug)]
+pub struct Decimator {
+    factor: u16,
+    mode: Decimation,
+    sum: (f32, f32, f32),
+    count: u16,
+}
+
+impl Decimator {
+    /// A `factor` of 0 is treated as 1
+    pub fn new(factor: u16, mode: Decimation) -> Self {
+        Self { factor: factor.max(1), mode, sum: (0.0, 0.0, 0.0), count: 0 }
+    }
+
+    /// Returns an output sample once every `factor` inputs
+    pub fn update(&mut self, input: (f32, f32, f32)) -> Option<(f32, f32, f32)> {
+        self.sum = match self.mode {
+            Decimation::Keep => input,
+            Decimation::Average => {
+                (self.sum.0 + input.0, self.sum.1 + input.1, self.sum.2 + input.2)
+            }
+        };
+        self.count += 1;
+        if self.count < self.factor {
+            return None;
+        }
+        let output = match self.mode {
+            Decimation::Keep => self.sum,
+            Decimation::Average => {
+                let n = self.factor as f32;
+                (self.sum.0 / n, self.sum.1 / n, self.sum.2 / n)
+            }
+        };
+        self.reset();
+        Some(output)
+    }
+
+    pub fn reset(&mut self) {
+        self.sum = (0.0, 0.0, 0.0);
+        self.count = 0;
+    }
+}
+
+/// Software filter chain the driver applies to scaled gyro reads
+#[derive(Default)]
+pub(crate) struct GyroFilter {
+    sample_rate: f32,
+    lowpass: Option<(f32, AxisFilter<LowPass>)>,
+    notch: Option<(f32, f32, AxisFilter<Biquad>)>,
+}
+
+impl GyroFilter {
+    pub(crate) fn set_lowpass(&mut self, cutoff: f32, stages: usize) {
+        let lowpass = LowPass::new(cutoff, self.sample_rate, stages);
+        self.lowpass = Some((cutoff, AxisFilter::new(lowpass)));
+    }
+
+    pub(crate) fn set_lowpass_cutoff(&mut self, cutoff: f32) {
+        let sample_rate = self.sample_rate;
+        if let Some((current, filter)) = &mut self.lowpass {
+            *current = cutoff;
+            filter.0.iter_mut().for_each(|lowpass| lowpass.set_cutoff(cutoff, sample_rate));
+        }
+    }
+
+    pub(crate) fn disable_lowpass(&mut self) {
+        self.lowpass = None;
+    }
+
+    pub(crate) fn set_notch(&mut self, center: f32, q: f32) {
+        let sample_rate = self.sample_rate;
+        match &mut self.notch {
+            Some((current_center, current_q, filter)) => {
+                *current_center = center;
+                *current_q = q;
+                let design = Biquad::notch(center, sample_rate, q);
+                filter.0.iter_mut().for_each(|notch| notch.retune(&design));
+            }
+            None => {
+                let notch = Biquad::notch(center, sample_rate, q);
+                self.notch = Some((center, q, AxisFilter::new(notch)));
+            }
+        }
+    }
+
+    pub(crate) fn disable_notch(&mut self) {
+        self.notch = None;
+    }
+
+    pub(crate) fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate;
+        if let Some((cutoff, _)) = self.lowpass {
+            self.set_lowpass_cutoff(cutoff);
+        }
+        if let Some((center, q, _)) = self.notch {
+            self.set_notch(center, q);
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.lowpass.is_some() || self.notch.is_some()
+    }
+
+    pub(crate) fn update(&mut self, rates: (f32, f32, f32)) -> (f32, f32, f32) {
+        let rates = match &mut self.notch {
+            Some((_, _, notch)) => notch.update(rates),
+            None => rates,
+        };
+        match &mut self.lowpass {
+            Some((_, lowpass)) => lowpass.update(rates),
+            None => rates,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_moving_average() {
+        use super::{AxisFilter, Filter, MovingAverage};
+
+        let mut average = MovingAverage::<4>::new();
+        assert_eq!(average.update(4.0), 4.0);
+        assert_eq!(average.update(2.0), 3.0);
+        average.update(0.0);
+        average.update(2.0);
+        assert_eq!(average.update(6.0), 2.5);
+
+        let mut axes = AxisFilter::new(MovingAverage::<2>::new());
+        axes.update((1.0, 2.0, 3.0));
+        assert_eq!(axes.update((3.0, 4.0, 5.0)), (2.0, 3.0, 4.0));
+    }
+
+    #[test]
+    fn test_lowpass() {
+        use super::{Filter, LowPass};
+
+        let mut lowpass = LowPass::new(100.0, 1000.0, 2);
+        let output = (0..200).fold(0.0, |_, _| lowpass.update(1.0));
+        assert!((output - 1.0).abs() < 1e-3);
+
+        let mut lowpass = LowPass::new(10.0, 1000.0, 1);
+        let peak = (0..200).map(|i| lowpass.update(if i % 2 == 0 { 1.0 } else { -1.0 }));
+        assert!(peak.skip(100).all(|value: f32| value.abs() < 1e-2));
+    }
+
+    #[test]
+    fn test_decimator() {
+        use super::{Decimation, Decimator};
+
+        let mut keep = Decimator::new(2, Decimation::Keep);
+        let mut average = Decimator::new(2, Decimation::Average);
+        assert_eq!(keep.update((1.0, 2.0, 3.0)), None);
+        assert_eq!(average.update((1.0, 2.0, 3.0)), None);
+        assert_eq!(keep.update((3.0, 4.0, 5.0)), Some((3.0, 4.0, 5.0)));
+        assert_eq!(average.update((3.0, 4.0, 5.0)), Some((2.0, 3.0, 4.0)));
+    }
+
+    #[test]
+    fn test_notch() {
+        use super::{Biquad, Filter};
+
+        let mut notch = Biquad::notch(250.0, 1000.0, 2.0);
+        // 250Hz at 1kHz sampling is the sequence 0, 1, 0, -1
+        let output = [0.0, 1.0, 0.0, -1.0].iter().cycle().take(400).map(|&v| notch.update(v));
+        assert!(output.skip(300).all(|value: f32| value.abs() < 1e-3));
+
+        let mut notch = Biquad::notch(250.0, 1000.0, 2.0);
+        let output = (0..100).fold(0.0, |_, _| notch.update(1.0));
+        assert!((output - 1.0).abs() < 1e-3);
+    }
+}
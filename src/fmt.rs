@@ -0,0 +1,90 @@
+//! Internal logging macros, forwarding to `log` or `defmt` when either feature is enabled
+
+#![allow(unused_macros)]
+
+#[cfg(all(feature = "log", feature = "defmt"))]
+compile_error!("features `log` and `defmt` are mutually exclusive");
+
+#[cfg(feature = "log")]
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        ::log::trace!($($arg)+)
+    };
+}
+
+#[cfg(feature = "log")]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        ::log::debug!($($arg)+)
+    };
+}
+
+#[cfg(feature = "log")]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        ::log::warn!($($arg)+)
+    };
+}
+
+#[cfg(feature = "log")]
+macro_rules! error {
+    ($($arg:tt)+) => {
+        ::log::error!($($arg)+)
+    };
+}
+
+#[cfg(feature = "defmt")]
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        ::defmt::trace!($($arg)+)
+    };
+}
+
+#[cfg(not(any(feature = "log", feature = "defmt")))]
+macro_rules! trace {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {{
+        $(let _ = &$arg;)*
+    }};
+}
+
+#[cfg(feature = "defmt")]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        ::defmt::debug!($($arg)+)
+    };
+}
+
+#[cfg(not(any(feature = "log", feature = "defmt")))]
+macro_rules! debug {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {{
+        $(let _ = &$arg;)*
+    }};
+}
+
+#[cfg(feature = "defmt")]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        ::defmt::warn!($($arg)+)
+    };
+}
+
+#[cfg(not(any(feature = "log", feature = "defmt")))]
+macro_rules! warn {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {{
+        $(let _ = &$arg;)*
+    }};
+}
+
+#[cfg(feature = "defmt")]
+macro_rules! error {
+    ($($arg:tt)+) => {
+        ::defmt::error!($($arg)+)
+    };
+}
+
+#[cfg(not(any(feature = "log", feature = "defmt")))]
+macro_rules! error {
+    ($fmt:literal $(, $arg:expr)* $(,)?) => {{
+        $(let _ = &$arg;)*
+    }};
+}
@@ -0,0 +1,151 @@
+//! Sensor health monitoring
+
+use crate::measurement::Temperature;
+use crate::variant::Variant;
+
+/// Result of `MPU6000::check_bus`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BusCheck {
+    /// Every test pattern read back intact
+    Ok,
+    /// Every read returned the same idle level, 0x00 or 0xff: nothing answers,
+    /// e.g. chip unpowered or missing, or chip select not wired
+    Absent,
+    /// The device answers but a pattern came back altered, pointing at wiring,
+    /// signal integrity or level shifter problems
+    Mismatch { written: u8, read: u8 },
+}
+
+/// Alarm limits in centi-celcius, matching `Temperature::centi_celcius_for`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TemperatureThresholds {
+    pub low: i16,
+    pub high: i16,
+    /// How far back inside the limits the temperature must be before the alarm clears
+    pub hysteresis: i16,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TemperatureAlarm {
+    High(Temperature),
+    Low(Temperature),
+    Cleared(Temperature),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum TemperatureState {
+    Normal,
+    High,
+    Low,
+}
+
+/// Reports a `TemperatureAlarm` each time a threshold is crossed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TemperatureMonitor {
+    thresholds: TemperatureThresholds,
+    state: TemperatureState,
+}
+
+impl TemperatureMonitor {
+    pub fn new(thresholds: TemperatureThresholds) -> Self {
+        Self { thresholds, state: TemperatureState::Normal }
+    }
+
+    /// `variant` selects the raw to celcius conversion
+    pub fn update(
+        &mut self,
+        temperature: Temperature,
+        variant: Variant,
+    ) -> Option<TemperatureAlarm> {
+        let centi = temperature.centi_celcius_for(variant);
+        let TemperatureThresholds { low, high, hysteresis } = self.thresholds;
+        let state = match self.state {
+            _ if centi > high => TemperatureState::High,
+            _ if centi < low => TemperatureState::Low,
+            TemperatureState::High if centi > high.saturating_sub(hysteresis) => self.state,
+            TemperatureState::Low if centi < low.saturating_add(hysteresis) => self.state,
+            _ => TemperatureState::Normal,
+        };
+        if state == self.state {
+            return None;
+        }
+        self.state = state;
+        Some(match state {
+            TemperatureState::High => TemperatureAlarm::High(temperature),
+            TemperatureState::Low => TemperatureAlarm::Low(temperature),
+            TemperatureState::Normal => TemperatureAlarm::Cleared(temperature),
+        })
+    }
+}
+
+/// Counts identical consecutive raw sensor bursts, fed only with bursts read after
+/// data ready was set. Noise makes two new samples in a row rarely equal on a live
+/// device, a run of them points at a wedged device or a broken MISO line. Re-reads
+/// of one sample when polling faster than the output data rate are always equal.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LockUpDetector {
+    limit: u16,
+    repeats: u16,
+    previous: Option<[u8; 14]>,
+}
+
+impl LockUpDetector {
+    /// Reports after `limit` bursts equal to the one before them
+    pub fn new(limit: u16) -> Self {
+        Self { limit: limit.max(1), repeats: 0, previous: None }
+    }
+
+    /// Feeds the 14 data bytes of a burst read, returns true once per run when it
+    /// reaches the limit
+    pub fn update(&mut self, burst: &[u8; 14]) -> bool {
+        if self.previous.replace(*burst) != Some(*burst) {
+            self.repeats = 0;
+            return false;
+        }
+        self.repeats = self.repeats.saturating_add(1);
+        self.repeats == self.limit
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_temperature_monitor() {
+        use super::{TemperatureAlarm, TemperatureMonitor, TemperatureThresholds};
+        use crate::measurement::Temperature;
+        use crate::variant::Variant;
+
+        // raw value for a given centi-celcius reading
+        let temperature = |centi: i32| Temperature(((centi - 3653) * 340 / 100) as i16);
+        let thresholds = TemperatureThresholds { low: 0, high: 6000, hysteresis: 200 };
+        let mut monitor = TemperatureMonitor::new(thresholds);
+        assert_eq!(monitor.update(temperature(2500), Variant::Mpu6000), None);
+        assert_eq!(
+            monitor.update(temperature(6100), Variant::Mpu6000),
+            Some(TemperatureAlarm::High(temperature(6100)))
+        );
+        assert_eq!(monitor.update(temperature(5900), Variant::Mpu6000), None);
+        assert_eq!(
+            monitor.update(temperature(5700), Variant::Mpu6000),
+            Some(TemperatureAlarm::Cleared(temperature(5700)))
+        );
+        assert_eq!(
+            monitor.update(temperature(-100), Variant::Mpu6000),
+            Some(TemperatureAlarm::Low(temperature(-100)))
+        );
+    }
+
+    #[test]
+    fn test_lock_up_detector() {
+        let mut detector = super::LockUpDetector::new(2);
+        let burst = [7u8; 14];
+        assert!(!detector.update(&burst));
+        assert!(!detector.update(&burst));
+        assert!(detector.update(&burst));
+        assert!(!detector.update(&burst));
+        assert!(!detector.update(&[0; 14]));
+        assert!(!detector.update(&[0; 14]));
+        assert!(detector.update(&[0; 14]));
+    }
+}
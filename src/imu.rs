@@ -0,0 +1,73 @@
+//! Driver independent 6-DoF IMU interface
+//!
+//! Application code written against `SixDofImu` can swap the MPU6000 for another
+//! accelerometer/gyro combination that implements the trait.
+
+use crate::bus::RegAccess;
+use crate::calibration::CalibrationData;
+use crate::measurement::ImuSample;
+use crate::registers::{AccelerometerRange, GyroRange};
+use crate::MPU6000;
+
+pub trait SixDofImu {
+    type Error;
+    type AccelerometerRange;
+    type GyroRange;
+    type Calibration;
+
+    /// Raw accelerometer, temperature and gyro counts taken at the same instant
+    fn read_sample(&mut self) -> Result<ImuSample, Self::Error>;
+
+    /// Acceleration in g
+    fn read_accel(&mut self) -> Result<(f32, f32, f32), Self::Error>;
+
+    /// Angular rate in °/s
+    fn read_gyro(&mut self) -> Result<(f32, f32, f32), Self::Error>;
+
+    fn set_accelerometer_range(
+        &mut self,
+        range: Self::AccelerometerRange,
+    ) -> Result<(), Self::Error>;
+
+    fn set_gyro_range(&mut self, range: Self::GyroRange) -> Result<(), Self::Error>;
+
+    /// Calibration currently applied to scaled reads
+    fn calibration(&self) -> Self::Calibration;
+
+    fn set_calibration(&mut self, calibration: Self::Calibration);
+}
+
+impl<E, BUS: RegAccess<Error = E>> SixDofImu for MPU6000<BUS> {
+    type Error = E;
+    type AccelerometerRange = AccelerometerRange;
+    type GyroRange = GyroRange;
+    type Calibration = CalibrationData;
+
+    fn read_sample(&mut self) -> Result<ImuSample, E> {
+        MPU6000::read_sample(self)
+    }
+
+    fn read_accel(&mut self) -> Result<(f32, f32, f32), E> {
+        MPU6000::read_accel(self)
+    }
+
+    fn read_gyro(&mut self) -> Result<(f32, f32, f32), E> {
+        MPU6000::read_gyro(self)
+    }
+
+    fn set_accelerometer_range(&mut self, range: AccelerometerRange) -> Result<(), E> {
+        MPU6000::set_accelerometer_range(self, range)
+    }
+
+    fn set_gyro_range(&mut self, range: GyroRange) -> Result<(), E> {
+        MPU6000::set_gyro_range(self, range)
+    }
+
+    fn calibration(&self) -> CalibrationData {
+        MPU6000::calibration(self)
+    }
+
+    fn set_calibration(&mut self, calibration: CalibrationData) {
+        MPU6000::set_calibration(self, calibration)
+    }
+}
@@ -0,0 +1,166 @@
+//! Interrupt status decoding and dispatch to per-source handlers
+
+use crate::bus::RegAccess;
+use crate::profile::Profile;
+use crate::variant::Variant;
+use crate::{Interrupt, MPU6000};
+
+/// Snapshot of INT_STATUS
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InterruptStatus(pub u8);
+
+impl InterruptStatus {
+    pub fn is_set(&self, interrupt: Interrupt) -> bool {
+        self.0 & 1 << interrupt as u8 != 0
+    }
+
+    pub fn data_ready(&self) -> bool {
+        self.is_set(Interrupt::DataReady)
+    }
+
+    pub fn motion(&self) -> bool {
+        self.is_set(Interrupt::Motion)
+    }
+
+    pub fn fifo_overflow(&self) -> bool {
+        self.is_set(Interrupt::FifoOverflow)
+    }
+
+    pub fn i2c_master(&self) -> bool {
+        self.is_set(Interrupt::I2cMaster)
+    }
+
+    /// DMP packet ready, see `Interrupt::Dmp`
+    pub fn dmp(&self) -> bool {
+        self.is_set(Interrupt::Dmp)
+    }
+
+    /// Latched FSYNC assertion, FSYNC_INT in bit 3 on MPU6500 class parts. `None` on
+    /// other variants, where bit 3 is the I2C master interrupt and FSYNC is only
+    /// reported through `MPU6000::read_fsync_interrupt`.
+    pub fn fsync(&self, variant: Variant) -> Option<bool> {
+        if !variant.is_6500_class() {
+            return None;
+        }
+        Some(self.0 & 1 << 3 != 0)
+    }
+}
+
+/// Reads INT_STATUS once and calls the handler of every pending source,
+/// meant to be called from the INT pin ISR.
+///
+/// For FSYNC, MPU6500 class parts use the status already read, other variants read
+/// I2C_MST_STATUS, only when `fsync` has a handler. `i2c_master` is not called on
+/// MPU6500 class parts, where its status bit reports FSYNC.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct InterruptDispatcher {
+    pub data_ready: Option<fn()>,
+    pub motion: Option<fn()>,
+    pub fifo_overflow: Option<fn()>,
+    pub i2c_master: Option<fn()>,
+    pub dmp: Option<fn()>,
+    pub fsync: Option<fn()>,
+}
+
+impl InterruptDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the status read, so sources without a handler can still be inspected
+    pub fn dispatch<E, BUS, P>(&self, mpu6000: &mut MPU6000<BUS, P>) -> Result<InterruptStatus, E>
+    where
+        BUS: RegAccess<Error = E>,
+        P: Profile,
+    {
+        let status = mpu6000.read_interrupt_status()?;
+        let fsync = status.fsync(mpu6000.variant());
+        let handlers = [
+            (status.data_ready(), self.data_ready),
+            (status.motion(), self.motion),
+            (status.fifo_overflow(), self.fifo_overflow),
+            (fsync.is_none() && status.i2c_master(), self.i2c_master),
+            (status.dmp(), self.dmp),
+        ];
+        for (pending, handler) in handlers.iter() {
+            if let (true, Some(handler)) = (pending, handler) {
+                handler();
+            }
+        }
+        if let Some(handler) = self.fsync {
+            let fsync = match fsync {
+                Some(fsync) => fsync,
+                None => mpu6000.read_fsync_interrupt()?,
+            };
+            if fsync {
+                handler();
+            }
+        }
+        Ok(status)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_interrupt_status() {
+        use super::InterruptStatus;
+        use crate::variant::Variant;
+
+        let status = InterruptStatus(0b0101_0001);
+        assert!(status.data_ready() && status.motion() && status.fifo_overflow());
+        assert!(!status.i2c_master() && !status.dmp());
+        assert!(InterruptStatus(0b10).dmp());
+        assert_eq!(status.fsync(Variant::Mpu6000), None);
+        assert_eq!(InterruptStatus(0b1000).fsync(Variant::Mpu9250), Some(true));
+    }
+
+    /// Reads `.0` from every register
+    struct StatusBus(u8);
+
+    impl crate::bus::RegAccess for StatusBus {
+        type Error = ();
+
+        fn write(&mut self, _reg: crate::registers::Register, _value: u8) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn read(&mut self, _reg: crate::registers::Register) -> Result<u8, ()> {
+            Ok(self.0)
+        }
+
+        fn reads(&mut self, _reg: crate::registers::Register, output: &mut [u8]) -> Result<(), ()> {
+            output.iter_mut().for_each(|b| *b = self.0);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_dispatch_fsync() {
+        use super::InterruptDispatcher;
+        use crate::variant::Variant;
+        use crate::MPU6000;
+        use core::sync::atomic::{AtomicU32, Ordering};
+
+        static FSYNC: AtomicU32 = AtomicU32::new(0);
+        static I2C_MASTER: AtomicU32 = AtomicU32::new(0);
+        let dispatcher = InterruptDispatcher {
+            i2c_master: Some(|| {
+                I2C_MASTER.fetch_add(1, Ordering::Relaxed);
+            }),
+            fsync: Some(|| {
+                FSYNC.fetch_add(1, Ordering::Relaxed);
+            }),
+            ..Default::default()
+        };
+        let mut mpu6000 = MPU6000::new(StatusBus(0b1000));
+        mpu6000.set_variant(Variant::Mpu6500);
+        assert!(dispatcher.dispatch(&mut mpu6000).is_ok());
+        assert_eq!((FSYNC.load(Ordering::Relaxed), I2C_MASTER.load(Ordering::Relaxed)), (1, 0));
+
+        // I2C_MST_STATUS reads 0b1000 too, without PASS_THROUGH set
+        let mut mpu6000 = MPU6000::new(StatusBus(0b1000));
+        assert!(dispatcher.dispatch(&mut mpu6000).is_ok());
+        assert_eq!((FSYNC.load(Ordering::Relaxed), I2C_MASTER.load(Ordering::Relaxed)), (1, 1));
+    }
+}
@@ -1,26 +1,149 @@
+//! # Panics
+//!
+//! Driver methods, decoders and filters do not panic: arithmetic is checked,
+//! saturating or wrapping, and arguments the device cannot represent are rejected
+//! with `Error::InvalidArgument`. The exceptions are the `From<&[u8]>` and
+//! `From<&[i16]>` measurement conversions, which index the slice, and the `sensors`
+//! handles, which panic on re-entrant use of the shared `RefCell`.
+
 #![no_std]
 
-use embedded_hal::blocking::delay::DelayMs;
+use core::marker::PhantomData;
+
+use embedded_hal::blocking::delay::{DelayMs, DelayUs};
 use embedded_hal::spi::{Mode, MODE_3};
+use fugit::{Duration, MillisDurationU32};
+
+#[macro_use]
+mod fmt;
 
+pub mod attitude;
+pub mod auto_range;
+pub mod auto_sleep;
 pub mod bus;
+pub mod calibration;
+pub mod config;
+pub mod dead_reckoning;
+pub mod drift;
+pub mod encoding;
+mod error;
+pub mod event;
+pub mod fifo;
+pub mod filter;
+pub mod health;
+pub mod imu;
+pub mod interrupt;
+pub mod log_record;
+#[cfg(feature = "mavlink")]
+pub mod mavlink;
 pub mod measurement;
+pub mod orientation;
+pub mod power;
+pub mod profile;
 #[macro_use]
 pub mod registers;
+pub mod sampler;
+pub mod scalar;
+pub mod sensors;
+pub mod stats;
+pub mod units;
+pub mod variant;
+pub mod vibration;
 
+use attitude::{GravityAxis, LevelTrim};
+pub use auto_range::AutoRange;
+use auto_range::{RangeTracker, Step};
 use bus::RegAccess;
-pub use measurement::{Acceleration, Gyro, Temperature};
+pub use calibration::{
+    CalProgress, CalStage, CalibrationData, CalibrationPlan, GyroTemperatureModel, TemperatureSweep,
+};
+pub use config::{DriverState, Mpu6000Config, ResetTiming};
+pub use error::Error;
+pub use event::{EventQueue, ImuEvent};
+pub use fifo::{FifoFrame, FifoFrames, SensorDecimation};
+use filter::GyroFilter;
+pub use fugit;
+use health::{
+    BusCheck, LockUpDetector, TemperatureAlarm, TemperatureMonitor, TemperatureThresholds,
+};
+pub use imu::SixDofImu;
+pub use interrupt::{InterruptDispatcher, InterruptStatus};
+pub use measurement::{
+    Acceleration, CelsiusDisplay, ClipStats, Freshness, Gyro, ImuSample, SampleMean, Temperature,
+};
+pub use orientation::{Inversion, Orientation, Rotation};
+pub use power::RegisterSnapshot;
+use profile::{Profile, Runtime};
 use registers::*;
+pub use sampler::PeriodicSampler;
+pub use scalar::Scalar;
+pub use variant::{Variant, WhoAmISet};
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum IntPinConfig {
     IntReadClear = 4,
+    /// FSYNC interrupt active low when set, active high when clear
+    FsyncIntLevel = 3,
+    /// Route FSYNC edges to the INT pin
+    FsyncIntEnable = 2,
+    /// Connect the auxiliary I2C bus to the host bus, only while the I2C master is off
+    I2cBypassEnable = 1,
+}
+
+/// Every field of INT_PIN_CFG, see `MPU6000::set_int_pin_settings`
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct IntPinSettings {
+    pub active_low: bool,
+    pub open_drain: bool,
+    pub mode: InterruptMode,
+    /// FSYNC interrupt active low
+    pub fsync_active_low: bool,
+    /// Route FSYNC edges to the INT pin
+    pub fsync_interrupt: bool,
+    /// Connect the auxiliary I2C bus to the host bus, disables the I2C master
+    pub i2c_bypass: bool,
+}
+
+impl From<IntPinSettings> for u8 {
+    fn from(settings: IntPinSettings) -> u8 {
+        (settings.active_low as u8) << 7
+            | (settings.open_drain as u8) << 6
+            | (settings.mode as u8) << 4
+            | (settings.fsync_active_low as u8) << 3
+            | (settings.fsync_interrupt as u8) << 2
+            | (settings.i2c_bypass as u8) << 1
+    }
+}
+
+/// INT pin behaviour, LATCH_INT_EN and INT_RD_CLEAR of INT_PIN_CFG
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum InterruptMode {
+    /// 50us pulse per interrupt
+    #[default]
+    Pulse50us = 0b00,
+    /// Held until any register read
+    LatchedClearOnAnyRead = 0b11,
+    /// Held until INT_STATUS is read
+    LatchedClearOnStatusRead = 0b10,
 }
 
+register_value!(InterruptMode { Pulse50us, LatchedClearOnAnyRead, LatchedClearOnStatusRead });
+
+/// Bit positions in INT_ENABLE and INT_STATUS
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Interrupt {
+    Motion = 6,
+    FifoOverflow = 4,
+    I2cMaster = 3,
+    /// DMP_INT, raised by DMP firmware when a packet is ready. This driver does not
+    /// load DMP firmware, so it only fires with firmware loaded by the application.
+    Dmp = 1,
     DataReady = 0,
 }
 
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum ClockSource {
+    #[default]
     Internal = 0,
     PLLGyroX = 1,
     PLLGyroY = 2,
@@ -30,9 +153,44 @@ pub enum ClockSource {
     Stop = 7,
 }
 
+register_value!(ClockSource {
+    Internal,
+    PLLGyroX,
+    PLLGyroY,
+    PLLGyroZ,
+    PLLExternal32_768KHz,
+    PLLExternal19_2MHz,
+    Stop
+});
+
+impl ClockSource {
+    /// Decodes CLKSEL, `None` for the reserved value
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        match bits & 0x7 {
+            0 => Some(ClockSource::Internal),
+            1 => Some(ClockSource::PLLGyroX),
+            2 => Some(ClockSource::PLLGyroY),
+            3 => Some(ClockSource::PLLGyroZ),
+            4 => Some(ClockSource::PLLExternal32_768KHz),
+            5 => Some(ClockSource::PLLExternal19_2MHz),
+            7 => Some(ClockSource::Stop),
+            _ => None,
+        }
+    }
+}
+
 pub const SPI_MODE: Mode = MODE_3;
 
-#[derive(Default)]
+/// I2C_MST_EN bit of USER_CTRL
+const I2C_MASTER_ENABLE: u8 = 5;
+
+/// Readings averaged for each point of `capture_temperature_sweep`
+const SWEEP_AVERAGE: u16 = 16;
+
+/// FIFO buffer size in bytes of the MPU6000/6050, see `Variant::fifo_size`
+pub const FIFO_SIZE: u16 = 1024;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct FifoEnable {
     pub temperature: bool,
     pub x_g_force: bool,
@@ -44,103 +202,725 @@ pub struct FifoEnable {
     pub slave0: bool,
 }
 
-impl Into<u8> for FifoEnable {
-    fn into(self) -> u8 {
-        (self.temperature as u8) << 7
-            | (self.x_g_force as u8) << 6
-            | (self.y_g_force as u8) << 5
-            | (self.z_g_force as u8) << 4
-            | (self.acceleration as u8) << 3
-            | (self.slave2 as u8) << 2
-            | (self.slave1 as u8) << 1
-            | (self.slave0 as u8) << 0
+impl FifoEnable {
+    /// Every sensor including the slaves
+    pub const fn all() -> Self {
+        Self::from_bits(0xff)
+    }
+
+    pub const fn none() -> Self {
+        Self::from_bits(0)
+    }
+
+    /// Decodes the FIFO_EN register value
+    pub const fn from_bits(bits: u8) -> Self {
+        Self {
+            temperature: bits & 1 << 7 != 0,
+            x_g_force: bits & 1 << 6 != 0,
+            y_g_force: bits & 1 << 5 != 0,
+            z_g_force: bits & 1 << 4 != 0,
+            acceleration: bits & 1 << 3 != 0,
+            slave2: bits & 1 << 2 != 0,
+            slave1: bits & 1 << 1 != 0,
+            slave0: bits & 1 != 0,
+        }
+    }
+
+    pub const fn with_temperature(mut self) -> Self {
+        self.temperature = true;
+        self
+    }
+
+    /// All three gyro axes
+    pub const fn with_gyro(mut self) -> Self {
+        self.x_g_force = true;
+        self.y_g_force = true;
+        self.z_g_force = true;
+        self
+    }
+
+    pub const fn with_accel(mut self) -> Self {
+        self.acceleration = true;
+        self
+    }
+}
+
+impl From<u8> for FifoEnable {
+    fn from(bits: u8) -> Self {
+        Self::from_bits(bits)
+    }
+}
+
+impl core::ops::BitOr for FifoEnable {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self::from_bits(u8::from(self) | u8::from(other))
+    }
+}
+
+impl From<FifoEnable> for u8 {
+    fn from(fifo_enable: FifoEnable) -> u8 {
+        u8::from(&fifo_enable)
+    }
+}
+
+impl From<&FifoEnable> for u8 {
+    fn from(fifo_enable: &FifoEnable) -> u8 {
+        (fifo_enable.temperature as u8) << 7
+            | (fifo_enable.x_g_force as u8) << 6
+            | (fifo_enable.y_g_force as u8) << 5
+            | (fifo_enable.z_g_force as u8) << 4
+            | (fifo_enable.acceleration as u8) << 3
+            | (fifo_enable.slave2 as u8) << 2
+            | (fifo_enable.slave1 as u8) << 1
+            | fifo_enable.slave0 as u8
     }
 }
 
-pub struct MPU6000<BUS> {
+pub struct MPU6000<BUS, P = Runtime> {
     bus: BUS,
-    dlpf_enabled: bool,
-    whoami: u8,
+    dlpf: u8,
+    sample_rate_divider: u8,
+    accepted_whoami: WhoAmISet,
+    product_id: ProductId,
+    variant: Variant,
+    gyro_bandwidth: GyroBandwidth,
+    fifo_enable: FifoEnable,
+    orientation: Orientation,
+    accelerometer_range: AccelerometerRange,
+    gyro_range: GyroRange,
+    clip_stats: ClipStats,
+    auto_range: Option<AutoRange>,
+    accelerometer_tracker: RangeTracker,
+    gyro_tracker: RangeTracker,
+    gyro_filter: GyroFilter,
+    calibration: CalibrationData,
+    temperature_monitor: Option<TemperatureMonitor>,
+    temperature_alarm: Option<TemperatureAlarm>,
+    last_temperature: Option<Temperature>,
+    fifo_temperature_interval: Option<MillisDurationU32>,
+    fifo_temperature_time: Option<fifo::Timestamp>,
+    previous_sample: Option<ImuSample>,
+    lock_up_detector: Option<LockUpDetector>,
+    lock_up: bool,
+    suspended: Option<RegisterSnapshot>,
+    wake_on_motion: Option<RegisterSnapshot>,
+    sensor_standby: Option<RegisterSnapshot>,
+    profile: PhantomData<P>,
 }
 
-impl<E, BUS: RegAccess<Error = E>> MPU6000<BUS> {
-    pub fn new(bus: BUS) -> Self {
-        MPU6000 { bus, dlpf_enabled: false, whoami: 0x68 }
+impl<E, BUS: RegAccess<Error = E>, P: Profile> MPU6000<BUS, P> {
+    /// Driver with measurement configuration fixed by `P`, call `apply_profile`
+    /// after reset to write it to the device
+    pub fn with_profile(bus: BUS) -> Self {
+        let defaults = Mpu6000Config::default();
+        let mut mpu6000 = MPU6000 {
+            bus,
+            dlpf: P::DLPF.unwrap_or(defaults.dlpf),
+            sample_rate_divider: P::SAMPLE_RATE_DIVIDER.unwrap_or(defaults.sample_rate_divider),
+            accepted_whoami: WhoAmISet::default(),
+            product_id: ProductId::Unknown,
+            variant: Variant::default(),
+            gyro_bandwidth: GyroBandwidth::Dlpf,
+            fifo_enable: FifoEnable::default(),
+            orientation: Orientation::default(),
+            accelerometer_range: P::ACCELEROMETER_RANGE.unwrap_or(defaults.accelerometer_range),
+            gyro_range: P::GYRO_RANGE.unwrap_or(defaults.gyro_range),
+            clip_stats: ClipStats::default(),
+            auto_range: None,
+            accelerometer_tracker: RangeTracker::default(),
+            gyro_tracker: RangeTracker::default(),
+            gyro_filter: GyroFilter::default(),
+            calibration: CalibrationData::default(),
+            temperature_monitor: None,
+            temperature_alarm: None,
+            last_temperature: None,
+            fifo_temperature_interval: None,
+            fifo_temperature_time: None,
+            previous_sample: None,
+            lock_up_detector: None,
+            lock_up: false,
+            suspended: None,
+            wake_on_motion: None,
+            sensor_standby: None,
+            profile: PhantomData,
+        };
+        mpu6000.gyro_filter.set_sample_rate(mpu6000.sample_rate() as f32);
+        mpu6000
+    }
+
+    /// Writes the settings fixed by the profile
+    pub fn apply_profile(&mut self) -> Result<(), E> {
+        if let Some(dlpf) = P::DLPF {
+            self.write_dlpf(dlpf)?;
+        }
+        if let Some(divider) = P::SAMPLE_RATE_DIVIDER {
+            self.write_sample_rate_divider(divider)?;
+        }
+        if let Some(range) = P::ACCELEROMETER_RANGE {
+            self.write_accelerometer_range(range)?;
+        }
+        if let Some(range) = P::GYRO_RANGE {
+            self.write_gyro_range(range)?;
+        }
+        Ok(())
     }
 
     pub fn set_register(&mut self, reg: Register, offset: u8, len: u8, bits: u8) -> Result<(), E> {
-        let mut value = self.bus.read(reg)?;
-        let mask = (1u8 << len) - 1;
-        value &= !(mask << offset);
-        value |= (bits & mask) << offset;
-        self.bus.write(reg, value)
+        let value = self.bus.read(reg)?;
+        self.bus.write(reg, encoding::set_field(value, offset, len, bits))
+    }
+
+    /// WHO_AM_I values `verify` accepts, e.g. `WhoAmISet::KNOWN` for clones
+    pub fn set_accepted_whoami(&mut self, accepted: WhoAmISet) {
+        self.accepted_whoami = accepted
     }
 
-    pub fn set_slave_address(&mut self, address: u8) {
-        self.whoami = address
+    pub fn accepted_whoami(&self) -> WhoAmISet {
+        self.accepted_whoami
     }
 
     pub fn whoami(&mut self) -> Result<u8, E> {
         self.bus.read(Register::WhoAmI)
     }
 
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Declares the connected device, enabling the registers specific to it
+    pub fn set_variant(&mut self, variant: Variant) {
+        debug!("set variant {:?}", variant);
+        self.variant = variant;
+    }
+
+    /// Sets the variant from WHO_AM_I, `None` and unchanged if unrecognized.
+    /// An MPU6050 is reported as MPU6000.
+    pub fn detect_variant(&mut self) -> Result<Option<Variant>, E> {
+        let variant = Variant::from_whoami(self.whoami()?);
+        if let Some(variant) = variant {
+            self.set_variant(variant);
+        }
+        Ok(variant)
+    }
+
     pub fn product_id(&mut self) -> Result<u8, E> {
         self.bus.read(Register::ProductId)
     }
 
+    /// Reads WHO_AM_I, `Some` with the value if it is accepted
+    pub fn verify_whoami(&mut self) -> Result<Option<u8>, E> {
+        let accepted = self.accepted_whoami;
+        self.verify_whoami_with(|whoami| accepted.contains(whoami))
+    }
+
+    /// Reads WHO_AM_I, `Some` with the value if `accept` returns true for it
+    pub fn verify_whoami_with<F: FnOnce(u8) -> bool>(
+        &mut self,
+        accept: F,
+    ) -> Result<Option<u8>, E> {
+        let whoami = self.whoami()?;
+        Ok(Some(whoami).filter(|&whoami| accept(whoami)))
+    }
+
+    /// Reads and decodes PRODUCT_ID, which selects the accelerometer scaling for
+    /// revisions C4 and C5, see `ProductId::accelerometer_half_scale`
+    pub fn detect_product_revision(&mut self) -> Result<ProductId, E> {
+        let product_id = self.product_id()?;
+        self.product_id = ProductId::from(product_id);
+        debug!("product id {:#x}", product_id);
+        Ok(self.product_id)
+    }
+
+    /// Revision decoded by `detect_product_revision` or `verify`
+    pub fn product_revision(&self) -> ProductId {
+        self.product_id
+    }
+
+    /// Checks WHO_AM_I and PRODUCT_ID, decoding the revision as `detect_product_revision`
     pub fn verify(&mut self) -> Result<bool, E> {
-        Ok(self.whoami()? == self.whoami && self.product_id()? != ProductId::Unknown as u8)
+        let whoami = self.whoami()?;
+        let product_id = self.product_id()?;
+        self.product_id = ProductId::from(product_id);
+        let verified =
+            self.accepted_whoami.contains(whoami) && product_id != ProductId::Unknown as u8;
+        if !verified {
+            warn!("verify failed, whoami {:#x} product id {:#x}", whoami, product_id);
+        }
+        Ok(verified)
     }
 
     /// Required when connected via BUS
-    pub fn reset<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), E> {
+    pub fn reset<D: DelayMs<u32>>(&mut self, delay: &mut D) -> Result<(), E> {
+        self.reset_with_timing(delay, ResetTiming::default())
+    }
+
+    /// `reset` with tuned waits, e.g. for fast boot
+    pub fn reset_with_timing<D: DelayMs<u32>>(
+        &mut self,
+        delay: &mut D,
+        timing: ResetTiming,
+    ) -> Result<(), E> {
+        delay.delay_ms(self.begin_reset(timing)?.to_millis());
+        delay.delay_ms(self.finish_reset(timing)?.to_millis());
+        Ok(())
+    }
+
+    /// First half of a non-blocking `reset`: issues DEVICE_RESET. Wait for the
+    /// returned `timing.device_reset`, doing other work meanwhile, then call
+    /// `finish_reset`.
+    pub fn begin_reset(&mut self, timing: ResetTiming) -> Result<MillisDurationU32, E> {
+        debug!("reset device");
         let reset_bit = PowerManagement1::DeviceReset as u8;
         self.bus.write(Register::PowerManagement1, reset_bit)?;
-        delay.delay_ms(150u8.into());
+        Ok(timing.device_reset)
+    }
 
+    /// Second half of a non-blocking `reset`: resets the signal paths. The device
+    /// is usable once the returned `timing.signal_path_reset` has passed.
+    pub fn finish_reset(&mut self, timing: ResetTiming) -> Result<MillisDurationU32, E> {
         let value = SignalPathReset::TemperatureReset as u8
             | SignalPathReset::AccelerometerReset as u8
             | SignalPathReset::GyroReset as u8;
         self.bus.write(Register::SignalPathReset, value)?;
-        delay.delay_ms(150u8.into());
-        Ok(())
+        debug!("reset done");
+        Ok(timing.signal_path_reset)
     }
 
     pub fn set_sleep(&mut self, enable: bool) -> Result<(), E> {
+        debug!("set sleep {}", enable);
         self.set_register(Register::PowerManagement1, 6, 1, enable as u8)?;
         Ok(())
     }
 
     pub fn set_clock_source(&mut self, source: ClockSource) -> Result<(), E> {
-        self.set_register(Register::PowerManagement1, 0, 3, source as u8)
+        let value = source as u8;
+        debug!("set clock source {}", value);
+        self.set_register(Register::PowerManagement1, 0, 3, value)
     }
 
-    pub fn set_dlpf(&mut self, value: u8) -> Result<(), E> {
-        self.dlpf_enabled = 0 < value && value < 7;
-        self.set_register(Register::Configuration, 0, 3, value as u8)
+    /// Clock source currently selected in PWR_MGMT_1, `None` if reserved
+    pub fn clock_source(&mut self) -> Result<Option<ClockSource>, E> {
+        Ok(ClockSource::from_bits(self.bus.read(Register::PowerManagement1)?))
+    }
+
+    /// Switches from the internal 8MHz oscillator to the PLL with gyro X reference,
+    /// which the datasheet recommends for accuracy, and reads the selection back.
+    /// Call after waking the device, returns whether the device accepted the PLL.
+    pub fn select_best_clock(&mut self) -> Result<bool, E> {
+        self.set_clock_source(ClockSource::PLLGyroX)?;
+        let accepted = self.clock_source()? == Some(ClockSource::PLLGyroX);
+        if !accepted {
+            warn!("PLL clock source not accepted, still on internal oscillator");
+        }
+        Ok(accepted)
+    }
+
+    /// Selects `source` and, for the external clock inputs, checks that the PLL locks:
+    /// the device must read back the selection and sensor data must keep updating.
+    /// Otherwise falls back to the PLL with gyro X reference and returns
+    /// `Error::ClockNotPresent`. The MPU6500 class has no external clock input and
+    /// returns `Error::Unsupported` for those without touching PWR_MGMT_1.
+    pub fn select_clock_source<D: DelayMs<u32>>(
+        &mut self,
+        source: ClockSource,
+        delay: &mut D,
+    ) -> Result<(), Error<E>> {
+        let external =
+            matches!(source, ClockSource::PLLExternal32_768KHz | ClockSource::PLLExternal19_2MHz);
+        if external && self.variant.is_6500_class() {
+            return Err(Error::Unsupported);
+        }
+        self.set_clock_source(source).map_err(Error::Bus)?;
+        if !external {
+            return Ok(());
+        }
+        // PLL settling time
+        delay.delay_ms(10);
+        let before = self.read_data_registers().map_err(Error::Bus)?;
+        let period_ms = 1_000 / self.sample_rate() as u32;
+        delay.delay_ms(period_ms.max(1) * 4);
+        let after = self.read_data_registers().map_err(Error::Bus)?;
+        let selected = self.clock_source().map_err(Error::Bus)? == Some(source);
+        if selected && before != after {
+            return Ok(());
+        }
+        warn!("external clock source {} not present", source as u8);
+        self.set_clock_source(ClockSource::PLLGyroX).map_err(Error::Bus)?;
+        Err(Error::ClockNotPresent)
+    }
+
+    /// Sensor data registers without clip tracking or temperature monitoring
+    fn read_data_registers(&mut self) -> Result<[u8; 14], E> {
+        let mut buffer = [0u8; 14];
+        self.bus.reads(Register::AccelerometerXHigh, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Whether the device is awake and producing data: the sleep bit must be clear
+    /// and, polling every millisecond within `timeout` and at least once, the sensor
+    /// data must change. Covers the 30ms gyro start-up after wake. INT_STATUS is not
+    /// read, so latched interrupt flags stay pending for their handler.
+    pub fn is_ready<D: DelayMs<u32>, const NOM: u32, const DENOM: u32>(
+        &mut self,
+        delay: &mut D,
+        timeout: Duration<u32, NOM, DENOM>,
+    ) -> Result<bool, E> {
+        if self.bus.read(Register::PowerManagement1)? & 1 << 6 != 0 {
+            debug!("not ready, asleep");
+            return Ok(false);
+        }
+        let initial = self.read_data_registers()?;
+        for _ in 0..timeout.to_millis().max(1) {
+            delay.delay_ms(1);
+            if self.read_data_registers()? != initial {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Checks whether PWR_MGMT_1 reads its power-on default, asleep on the internal
+    /// oscillator for the MPU6000/6050 and awake on the auto-selected clock for the
+    /// MPU6500 class, which means the device reset itself. Call while the device is
+    /// expected awake, a device put to sleep by `suspend` is not reported.
+    pub fn detect_unexpected_reset(&mut self) -> Result<Option<ImuEvent>, E> {
+        let power_on_default = if self.variant.is_6500_class() { 0x01 } else { 0x40 };
+        if self.suspended.is_some()
+            || self.bus.read(Register::PowerManagement1)? != power_on_default
+        {
+            return Ok(None);
+        }
+        warn!("unexpected device reset");
+        Ok(Some(ImuEvent::UnexpectedReset))
+    }
+
+    /// Writes test patterns to SMPLRT_DIV, reads them back and restores the original
+    /// value. Run at startup before configuring to tell bus faults from an absent chip.
+    pub fn check_bus(&mut self) -> Result<BusCheck, E> {
+        let original = self.bus.read(Register::SampleRateDivider)?;
+        let mut reads = [original; 3];
+        let mut mismatch = None;
+        for (&pattern, read) in [0x55u8, 0xaa].iter().zip(reads[1..].iter_mut()) {
+            self.bus.write(Register::SampleRateDivider, pattern)?;
+            *read = self.bus.read(Register::SampleRateDivider)?;
+            if *read != pattern && mismatch.is_none() {
+                mismatch = Some(BusCheck::Mismatch { written: pattern, read: *read });
+            }
+        }
+        self.bus.write(Register::SampleRateDivider, original)?;
+        let idle = reads.iter().all(|&read| read == 0) || reads.iter().all(|&read| read == 0xff);
+        let check = match mismatch {
+            Some(_) if idle => BusCheck::Absent,
+            Some(mismatch) => mismatch,
+            None => BusCheck::Ok,
+        };
+        if check != BusCheck::Ok {
+            warn!("bus check failed");
+        }
+        Ok(check)
     }
 
     pub fn set_i2c_disable(&mut self, disable: bool) -> Result<(), E> {
         self.set_register(Register::UserControl, 2, 1, disable as u8)
     }
 
-    /// set DLPF before set sample rate
-    pub fn set_sample_rate(&mut self, rate: u16) -> Result<(), E> {
-        let divider = if !self.dlpf_enabled { 8_000 } else { 1_000 } / rate - 1;
-        self.bus.write(Register::SampleRateDivider, divider as u8)
+    fn write_dlpf(&mut self, value: u8) -> Result<(), E> {
+        debug!("set dlpf {}", value);
+        self.set_register(Register::Configuration, 0, 3, value)?;
+        self.dlpf = value & 0x7;
+        self.gyro_filter.set_sample_rate(self.sample_rate() as f32);
+        Ok(())
+    }
+
+    fn write_sample_rate_divider(&mut self, divider: u8) -> Result<(), E> {
+        self.bus.write(Register::SampleRateDivider, divider)?;
+        self.sample_rate_divider = divider;
+        self.gyro_filter.set_sample_rate(self.sample_rate() as f32);
+        Ok(())
+    }
+
+    fn write_accelerometer_range(&mut self, range: AccelerometerRange) -> Result<(), E> {
+        debug!("set accelerometer range {:?}", range);
+        self.set_register(Register::AccelerometerConfig, 3, 2, range as u8)?;
+        self.accelerometer_range = range;
+        Ok(())
+    }
+
+    fn write_gyro_range(&mut self, range: GyroRange) -> Result<(), E> {
+        debug!("set gyro range {:?}", range);
+        self.set_register(Register::GyroConfig, 3, 2, range as u8)?;
+        self.gyro_range = range;
+        Ok(())
+    }
+
+    /// Enables accelerometer self-test actuation per axis, XA_ST/YA_ST/ZA_ST
+    pub fn set_accelerometer_self_test(&mut self, x: bool, y: bool, z: bool) -> Result<(), E> {
+        let bits = (x as u8) << 2 | (y as u8) << 1 | z as u8;
+        self.set_register(Register::AccelerometerConfig, 5, 3, bits)
+    }
+
+    /// Enables gyro self-test actuation per axis, XG_ST/YG_ST/ZG_ST
+    pub fn set_gyro_self_test(&mut self, x: bool, y: bool, z: bool) -> Result<(), E> {
+        let bits = (x as u8) << 2 | (y as u8) << 1 | z as u8;
+        self.set_register(Register::GyroConfig, 5, 3, bits)
+    }
+
+    /// Bypasses the gyro DLPF for 32kHz output, MPU6500 class parts only.
+    /// SMPLRT_DIV does not apply in the bypass modes.
+    pub fn set_gyro_bandwidth(&mut self, bandwidth: GyroBandwidth) -> Result<(), Error<E>> {
+        if !self.variant.is_6500_class() {
+            return Err(Error::Unsupported);
+        }
+        debug!("set gyro bandwidth {:?}", bandwidth);
+        self.set_register(Register::GyroConfig, 0, 2, bandwidth as u8).map_err(Error::Bus)?;
+        self.gyro_bandwidth = bandwidth;
+        self.gyro_filter.set_sample_rate(self.sample_rate() as f32);
+        Ok(())
+    }
+
+    /// Accelerometer bandwidth independent of the gyro DLPF, MPU6500 class parts only
+    pub fn set_accelerometer_dlpf(&mut self, dlpf: AccelerometerDlpf) -> Result<(), Error<E>> {
+        if !self.variant.is_6500_class() {
+            return Err(Error::Unsupported);
+        }
+        debug!("set accelerometer dlpf {:?}", dlpf);
+        self.set_register(Register::AccelerometerConfig2, 0, 4, dlpf as u8).map_err(Error::Bus)
+    }
+
+    /// ACCEL_HPF, the filtered path feeds motion detection only. MPU6000 and MPU6050
+    /// only, the bits are reserved on the MPU6500 class.
+    pub fn set_accelerometer_high_pass(
+        &mut self,
+        filter: AccelerometerHighPass,
+    ) -> Result<(), Error<E>> {
+        if self.variant.is_6500_class() {
+            return Err(Error::Unsupported);
+        }
+        self.write_accelerometer_high_pass(filter).map_err(Error::Bus)
+    }
+
+    fn write_accelerometer_high_pass(&mut self, filter: AccelerometerHighPass) -> Result<(), E> {
+        let value = filter as u8;
+        debug!("set accelerometer high pass {}", value);
+        self.set_register(Register::AccelerometerConfig, 0, 3, value)
+    }
+
+    /// Output data rate in Hz as configured through `set_dlpf` and `set_sample_rate`
+    pub fn sample_rate(&self) -> u16 {
+        if self.gyro_bandwidth != GyroBandwidth::Dlpf {
+            return 32_000;
+        }
+        let divider = P::SAMPLE_RATE_DIVIDER.unwrap_or(self.sample_rate_divider);
+        encoding::sample_rate(P::DLPF.unwrap_or(self.dlpf), divider)
+    }
+
+    pub fn config(&self) -> Mpu6000Config {
+        Mpu6000Config {
+            accelerometer_range: self.accelerometer_range(),
+            gyro_range: self.gyro_range(),
+            dlpf: P::DLPF.unwrap_or(self.dlpf),
+            sample_rate_divider: P::SAMPLE_RATE_DIVIDER.unwrap_or(self.sample_rate_divider),
+        }
+    }
+
+    /// Latches the FSYNC pin into the LSB of the chosen sensor output,
+    /// e.g. for camera frame synchronization
+    pub fn set_external_sync(&mut self, sync: ExtSync) -> Result<(), E> {
+        let value = sync as u8;
+        debug!("set external sync {}", value);
+        self.set_register(Register::Configuration, 3, 3, value)
+    }
+
+    /// Driver side state, see `DriverState`
+    pub fn state(&self) -> DriverState {
+        DriverState {
+            config: self.config(),
+            variant: self.variant,
+            gyro_bandwidth: self.gyro_bandwidth,
+            fifo_enable: self.fifo_enable,
+            orientation: self.orientation,
+            accepted_whoami: self.accepted_whoami,
+            product_id: self.product_id,
+            calibration: self.calibration,
+            auto_range: self.auto_range,
+            clip_stats: self.clip_stats,
+            temperature_monitor: self.temperature_monitor,
+        }
+    }
+
+    /// Rebuilds a driver from `state`, e.g. after the bus was reclaimed with `free`.
+    /// Nothing is written to the device.
+    pub fn from_state(bus: BUS, state: DriverState) -> Self {
+        let mut mpu6000 = Self::with_profile(bus);
+        mpu6000.accelerometer_range = state.config.accelerometer_range;
+        mpu6000.gyro_range = state.config.gyro_range;
+        mpu6000.dlpf = state.config.dlpf;
+        mpu6000.sample_rate_divider = state.config.sample_rate_divider;
+        mpu6000.variant = state.variant;
+        mpu6000.gyro_bandwidth = state.gyro_bandwidth;
+        mpu6000.fifo_enable = state.fifo_enable;
+        mpu6000.orientation = state.orientation;
+        mpu6000.accepted_whoami = state.accepted_whoami;
+        mpu6000.product_id = state.product_id;
+        mpu6000.calibration = state.calibration;
+        mpu6000.auto_range = state.auto_range;
+        mpu6000.clip_stats = state.clip_stats;
+        mpu6000.temperature_monitor = state.temperature_monitor;
+        mpu6000.gyro_filter.set_sample_rate(mpu6000.sample_rate() as f32);
+        mpu6000
     }
 
+    /// `IntPinConfig::I2cBypassEnable` goes through `set_i2c_bypass`
     pub fn set_int_pin_config(&mut self, pin_config: IntPinConfig, enable: bool) -> Result<(), E> {
+        if pin_config == IntPinConfig::I2cBypassEnable {
+            return self.set_i2c_bypass(enable);
+        }
         self.set_register(Register::IntPinConfig, pin_config as u8, 1, enable as u8)
     }
 
+    /// Writes the whole INT_PIN_CFG, turning the I2C master off first if bypass is set
+    pub fn set_int_pin_settings(&mut self, settings: &IntPinSettings) -> Result<(), E> {
+        if settings.i2c_bypass {
+            self.set_register(Register::UserControl, I2C_MASTER_ENABLE, 1, 0)?;
+        }
+        self.bus.write(Register::IntPinConfig, (*settings).into())
+    }
+
+    /// I2C_BYPASS_EN, the I2C master is turned off first when enabling
+    pub fn set_i2c_bypass(&mut self, enable: bool) -> Result<(), E> {
+        debug!("set i2c bypass {}", enable);
+        if enable {
+            self.set_register(Register::UserControl, I2C_MASTER_ENABLE, 1, 0)?;
+        }
+        self.set_register(
+            Register::IntPinConfig,
+            IntPinConfig::I2cBypassEnable as u8,
+            1,
+            enable as u8,
+        )
+    }
+
+    /// I2C_MST_EN, I2C bypass is turned off first when enabling
+    pub fn set_i2c_master_enable(&mut self, enable: bool) -> Result<(), E> {
+        debug!("set i2c master {}", enable);
+        if enable {
+            self.set_register(Register::IntPinConfig, IntPinConfig::I2cBypassEnable as u8, 1, 0)?;
+        }
+        self.set_register(Register::UserControl, I2C_MASTER_ENABLE, 1, enable as u8)
+    }
+
+    pub fn set_interrupt_mode(&mut self, mode: InterruptMode) -> Result<(), E> {
+        self.set_register(Register::IntPinConfig, 4, 2, mode as u8)
+    }
+
     pub fn set_interrupt_enable(&mut self, interrupt: Interrupt, enable: bool) -> Result<(), E> {
         self.set_register(Register::InterruptEnable, interrupt as u8, 1, enable as u8)
     }
 
+    /// Reads and, unless INT_RD_CLEAR is set, clears INT_STATUS
+    pub fn read_interrupt_status(&mut self) -> Result<InterruptStatus, E> {
+        Ok(InterruptStatus(self.bus.read(Register::IntStatus)?))
+    }
+
+    /// Reads and clears the FSYNC interrupt flag in I2C_MST_STATUS
+    pub fn read_fsync_interrupt(&mut self) -> Result<bool, E> {
+        Ok(self.bus.read(Register::I2cMasterStatus)? & 1 << 7 != 0)
+    }
+
     pub fn enable_fifo(&mut self, fifo_enable: FifoEnable) -> Result<(), E> {
         let value: u8 = fifo_enable.into();
-        self.bus.write(Register::FifoEnable, value)
+        debug!("enable fifo {:#x}", value);
+        self.bus.write(Register::FifoEnable, value)?;
+        self.fifo_enable = fifo_enable;
+        Ok(())
+    }
+
+    /// Sensors written to the FIFO, as last set with `enable_fifo`
+    pub fn fifo_enable(&self) -> FifoEnable {
+        self.fifo_enable
+    }
+
+    /// Decodes drained FIFO bytes in the board frame with per-frame timestamps at the
+    /// current sample rate, `drain_time` being when `read_fifo` returned
+    pub fn fifo_frames<'a>(&self, bytes: &'a [u8], drain_time: fifo::Timestamp) -> FifoFrames<'a> {
+        let frames =
+            FifoFrames::with_timestamps(&self.fifo_enable, bytes, drain_time, self.sample_rate())
+                .with_orientation(self.orientation);
+        match (self.fifo_temperature_interval, self.last_temperature) {
+            (Some(_), Some(temperature)) => frames.with_temperature(temperature),
+            _ => frames,
+        }
+    }
+
+    /// While the FIFO carries no temperature, `drain_fifo` reads the temperature
+    /// register at most every `interval` and `fifo_frames` attaches the latest reading
+    /// to each frame, e.g. for the gyro temperature model. `None` stops it.
+    pub fn set_fifo_temperature_interval(&mut self, interval: Option<MillisDurationU32>) {
+        self.fifo_temperature_interval = interval;
+        self.fifo_temperature_time = None;
+    }
+
+    /// `read_fifo` followed by `fifo_frames`, first reading the temperature out of band
+    /// if due, see `set_fifo_temperature_interval`
+    pub fn drain_fifo<'a>(
+        &mut self,
+        buffer: &'a mut [u8],
+        now: fifo::Timestamp,
+    ) -> Result<FifoFrames<'a>, E> {
+        if let Some(interval) = self.fifo_temperature_interval {
+            let due = self.fifo_temperature_time.map_or(true, |time| {
+                now.ticks().wrapping_sub(time.ticks()) >= interval.to_micros() as u64
+            });
+            if due && !self.fifo_enable.temperature {
+                self.read_temperature()?;
+                self.fifo_temperature_time = Some(now);
+            }
+        }
+        let len = self.read_fifo(buffer)?;
+        Ok(self.fifo_frames(&buffer[..len], now))
+    }
+
+    /// Polls once per sample period until at least `frames` whole frames are buffered,
+    /// returns the number of whole frames available
+    pub fn wait_for_fifo_level<D: DelayUs<u32>, const NOM: u32, const DENOM: u32>(
+        &mut self,
+        frames: u16,
+        delay: &mut D,
+        timeout: Duration<u32, NOM, DENOM>,
+    ) -> Result<u16, Error<E>> {
+        let size = fifo::frame_size(&self.fifo_enable) as u16;
+        let period = 1_000_000 / self.sample_rate() as u32;
+        let timeout = timeout.to_micros();
+        let mut elapsed = 0u32;
+        loop {
+            let count = self.get_fifo_counter().map_err(Error::Bus)?;
+            let available = count.checked_div(size).unwrap_or(0);
+            if available >= frames {
+                return Ok(available);
+            }
+            if elapsed >= timeout {
+                return Err(Error::Timeout);
+            }
+            delay.delay_us(period);
+            elapsed = elapsed.saturating_add(period);
+        }
+    }
+
+    /// Drains as many whole frames as fit in `buffer`, returns the number of bytes read.
+    /// Decode them with `FifoFrames::new(&mpu6000.fifo_enable(), &buffer[..len])`.
+    pub fn read_fifo(&mut self, buffer: &mut [u8]) -> Result<usize, E> {
+        let size = fifo::frame_size(&self.fifo_enable);
+        if size == 0 {
+            return Ok(0);
+        }
+        let available = (self.get_fifo_counter()? as usize).min(buffer.len());
+        let len = available - available % size;
+        if len > 0 {
+            self.bus.reads(Register::FifoReadWrite, &mut buffer[..len])?;
+        }
+        Ok(len)
     }
 
     pub fn enable_fifo_buffer(&mut self) -> Result<(), E> {
@@ -151,103 +931,857 @@ impl<E, BUS: RegAccess<Error = E>> MPU6000<BUS> {
     pub fn get_fifo_counter(&mut self) -> Result<u16, E> {
         let high = self.bus.read(Register::FifoCountHigh)?;
         let low = self.bus.read(Register::FifoCountLow)?;
-        return Ok((high as u16) << 8 | low as u16);
-    }
-
-    pub fn set_gyro_range(&mut self, range: GyroRange) -> Result<(), E> {
-        self.bus.write(Register::GyroConfig, (range as u8) << 3)
+        let count = (high as u16) << 8 | low as u16;
+        if count >= self.variant.fifo_size() {
+            warn!("fifo overflow");
+        }
+        Ok(count)
     }
 
-    pub fn read_acceleration(&mut self) -> Result<Acceleration, E> {
+    /// Raw accelerometer counts, see `read_accel` for values in g
+    pub fn read_accel_raw(&mut self) -> Result<Acceleration, E> {
         let mut buffer = [0u8; 6];
         self.bus.reads(Register::AccelerometerXHigh, &mut buffer)?;
-        Ok(buffer[..].into())
+        let acceleration = buffer[..].into();
+        self.track_acceleration(&acceleration)?;
+        Ok(self.orientation.acceleration(acceleration))
     }
 
-    pub fn read_gyro(&mut self) -> Result<Gyro, E> {
+    /// Acceleration in g, scaled with the range last set by `set_accelerometer_range`
+    pub fn read_accel(&mut self) -> Result<(f32, f32, f32), E> {
+        let scale_factor = self.accelerometer_scale_factor();
+        let acceleration = scalar::scale(self.read_accel_raw()?.to_array(), scale_factor);
+        Ok(self.calibration.level_trim.map_or(acceleration, |trim| trim.apply(acceleration)))
+    }
+
+    /// Raw gyro counts, see `read_gyro` for values in °/s
+    pub fn read_gyro_raw(&mut self) -> Result<Gyro, E> {
         let mut buffer = [0u8; 6];
         self.bus.reads(Register::GyroXHigh, &mut buffer)?;
-        Ok(buffer[..].into())
+        let gyro: Gyro = buffer[..].into();
+        self.track_gyro(&gyro)?;
+        Ok(self.orientation.gyro(gyro))
     }
 
-    pub fn read_temperature(&mut self) -> Result<Temperature, E> {
-        let mut buffer = [0u8; 2];
-        self.bus.reads(Register::AccelerometerXHigh, &mut buffer)?;
-        Ok(buffer[..].into())
+    /// Angular rate in °/s, scaled with the range last set by `set_gyro_range`
+    /// and passed through the software gyro filters
+    pub fn read_gyro(&mut self) -> Result<(f32, f32, f32), E> {
+        let range = self.gyro_range();
+        let (x, y, z) = self.read_gyro_raw()?.normalize(range);
+        let rates = match self.gyro_bias() {
+            Some((bx, by, bz)) => (x - bx, y - by, z - bz),
+            None => (x, y, z),
+        };
+        let rates = self.calibration.level_trim.map_or(rates, |trim| trim.apply(rates));
+        Ok(self.gyro_filter.update(rates))
     }
 
-    pub fn read_all(&mut self) -> Result<(Acceleration, Temperature, Gyro), E> {
-        let mut buffer = [0u8; 14];
-        self.bus.reads(Register::AccelerometerXHigh, &mut buffer)?;
-        Ok((buffer[..6].into(), buffer[6..8].into(), buffer[8..].into()))
+    /// `read_accel` in `T`, e.g. `f64`. The level trim is applied in f32 if set.
+    pub fn read_accel_as<T: Scalar>(&mut self) -> Result<(T, T, T), E> {
+        if self.calibration.level_trim.is_some() {
+            return Ok(scalar::convert(self.read_accel()?));
+        }
+        let scale_factor = self.accelerometer_scale_factor();
+        Ok(scalar::scale(self.read_accel_raw()?.to_array(), scale_factor))
     }
 
-    pub fn set_accelerometer_range(&mut self, range: AccelerometerRange) -> Result<(), E> {
-        self.bus.write(Register::AccelerometerConfig, (range as u8) << 3)
+    /// `read_gyro` in `T`, e.g. `f64`. The bias, level trim and gyro filters are
+    /// applied in f32 if set.
+    pub fn read_gyro_as<T: Scalar>(&mut self) -> Result<(T, T, T), E> {
+        let calibration = &self.calibration;
+        if calibration.level_trim.is_some()
+            || calibration.gyro_bias.is_some()
+            || calibration.gyro_temperature_model.is_some()
+            || self.gyro_filter.is_enabled()
+        {
+            return Ok(scalar::convert(self.read_gyro()?));
+        }
+        let range = self.gyro_range();
+        Ok(self.read_gyro_raw()?.normalize_as(range))
     }
-}
 
-impl<BUS> MPU6000<BUS> {
-    pub fn free(self) -> BUS {
-        self.bus
+    /// Acceleration in milli-g without floating point, for FPU-less parts.
+    /// The level trim is not applied.
+    pub fn read_acceleration_mg(&mut self) -> Result<[i32; 3], E> {
+        let (numerator, denominator) = self.accelerometer_range().scale_ratio();
+        let half_scale = self.product_id.accelerometer_half_scale();
+        let ratio =
+            if half_scale { (numerator, denominator * 2) } else { (numerator, denominator) };
+        Ok(measurement::to_milli(self.read_accel_raw()?.to_array(), ratio))
     }
-}
 
-mod test {
-    use embedded_hal::blocking::delay::{DelayMs, DelayUs};
-    use embedded_hal::blocking::spi::{Transfer, Write};
-    use embedded_hal::digital::v2::OutputPin;
+    /// Angular rate in milli-degrees per second without floating point, for FPU-less
+    /// parts. Gyro bias, level trim and software filters are not applied.
+    pub fn read_gyro_mdps(&mut self) -> Result<[i32; 3], E> {
+        let range = self.gyro_range();
+        Ok(self.read_gyro_raw()?.to_mdps(range))
+    }
 
-    struct StubSPI {}
+    /// Biquad butterworth low-pass of 1 or 2 stages on `read_gyro` output,
+    /// assumes `read_gyro` is called once per sample at `sample_rate`
+    pub fn enable_gyro_lowpass(&mut self, cutoff: f32, stages: usize) {
+        debug!("enable gyro lowpass {}Hz", cutoff);
+        self.gyro_filter.set_lowpass(cutoff, stages)
+    }
 
-    impl Write<u8> for StubSPI {
-        type Error = &'static str;
-        fn write(&mut self, _bytes: &[u8]) -> Result<(), &'static str> {
-            Ok(())
+    /// Retunes an enabled gyro low-pass without resetting its state
+    pub fn set_gyro_lowpass_cutoff(&mut self, cutoff: f32) {
+        self.gyro_filter.set_lowpass_cutoff(cutoff)
+    }
+
+    pub fn disable_gyro_lowpass(&mut self) {
+        self.gyro_filter.disable_lowpass()
+    }
+
+    /// Notch at `center` Hz ahead of the gyro low-pass, for rejecting frame resonance.
+    /// Calling it again retunes the notch without resetting its state.
+    pub fn set_gyro_notch(&mut self, center: f32, q: f32) {
+        debug!("set gyro notch {}Hz", center);
+        self.gyro_filter.set_notch(center, q)
+    }
+
+    pub fn disable_gyro_notch(&mut self) {
+        self.gyro_filter.disable_notch()
+    }
+
+    /// Averages `samples` accelerometer readings, one per sample period, while the
+    /// vehicle sits on a flat surface and returns its mounting tilt.
+    /// Pass the result to `set_level_trim` to correct future scaled reads.
+    pub fn measure_level<D: DelayUs<u32>>(
+        &mut self,
+        delay: &mut D,
+        samples: u16,
+    ) -> Result<LevelTrim, E> {
+        let trim = LevelTrim::from_gravity(self.sample_mean(samples, delay)?.acceleration);
+        debug!("measured level roll {} pitch {}", trim.roll, trim.pitch);
+        Ok(trim)
+    }
+
+    /// Captures the gyro bias and, if planned, the level trim with the device at rest,
+    /// replacing the previous results, including a gyro temperature model. Returns the
+    /// software calibration now in use, or `Error::Motion` if the device would not stay
+    /// still.
+    pub fn calibrate<D: DelayUs<u32>>(
+        &mut self,
+        delay: &mut D,
+        plan: CalibrationPlan,
+    ) -> Result<CalibrationData, Error<E>> {
+        let orientation = self.orientation;
+        if plan.hardware_offsets {
+            // the offset registers act before orientation, so capture in sensor axes
+            self.set_gyro_offsets([0; 3]).map_err(Error::Bus)?;
+            self.orientation = Orientation::default();
         }
+        let mean = self.capture_at_rest(delay, &plan, CalStage::GyroBias);
+        self.orientation = orientation;
+        let bias = mean?.gyro;
+        debug!("calibrated gyro bias {} {} {}", bias.0, bias.1, bias.2);
+        if plan.hardware_offsets {
+            self.set_gyro_offsets(calibration::gyro_offset_counts(bias)).map_err(Error::Bus)?;
+            self.calibration.gyro_bias = None;
+        } else {
+            self.calibration.gyro_bias = Some(bias);
+        }
+        self.calibration.gyro_temperature_model = None;
+        if plan.level {
+            let mean = self.capture_at_rest(delay, &plan, CalStage::Level)?;
+            self.calibration.level_trim = Some(LevelTrim::from_gravity(mean.acceleration));
+        }
+        plan.report(CalProgress::Complete);
+        Ok(self.calibration)
     }
 
-    impl Transfer<u8> for StubSPI {
-        type Error = &'static str;
-        fn transfer<'w>(&mut self, bytes: &'w mut [u8]) -> Result<&'w [u8], &'static str> {
-            for b in bytes.iter_mut() {
-                *b = 100;
+    /// `sample_mean` that starts over whenever the gyro moves past the plan's motion
+    /// threshold
+    fn capture_at_rest<D: DelayUs<u32>>(
+        &mut self,
+        delay: &mut D,
+        plan: &CalibrationPlan,
+        stage: CalStage,
+    ) -> Result<SampleMean, Error<E>> {
+        let period = 1_000_000 / self.sample_rate() as u32;
+        let total = plan.samples.max(1);
+        let limit = plan.motion_threshold.map(|rate| rate * self.gyro_range().scale_factor());
+        let mut restarts = 0;
+        'capture: loop {
+            let mut sum = [0i32; 7];
+            let mut reference = None;
+            for captured in 1..=total {
+                let (acceleration, temperature, gyro) = self.read_all().map_err(Error::Bus)?;
+                let first = *reference.get_or_insert(gyro);
+                let moved = |value: i16, first: i16| {
+                    limit.is_some_and(|limit| (value as f32 - first as f32).abs() > limit)
+                };
+                if moved(gyro.0, first.0) || moved(gyro.1, first.1) || moved(gyro.2, first.2) {
+                    if restarts == plan.max_restarts {
+                        warn!("calibration aborted, device not at rest");
+                        return Err(Error::Motion);
+                    }
+                    restarts += 1;
+                    plan.report(CalProgress::Restarted { stage, restarts });
+                    continue 'capture;
+                }
+                let fields = [
+                    acceleration.0,
+                    acceleration.1,
+                    acceleration.2,
+                    temperature.0,
+                    gyro.0,
+                    gyro.1,
+                    gyro.2,
+                ];
+                for (sum, value) in sum.iter_mut().zip(fields.iter()) {
+                    *sum += *value as i32;
+                }
+                plan.report(CalProgress::Sample { stage, captured, total });
+                if captured < total {
+                    delay.delay_us(period);
+                }
             }
-            Ok(bytes)
+            return Ok(self.scale_sum(sum, total));
         }
     }
 
-    struct StubOutputPin {}
-    impl OutputPin for StubOutputPin {
-        type Error = &'static str;
-        fn set_high(&mut self) -> Result<(), &'static str> {
-            Ok(())
+    /// Logs the gyro rate against temperature every `interval` for `readings` readings
+    /// while the device sits still and warms up, then stores the fitted
+    /// `GyroTemperatureModel` in the calibration. Returns `None`, keeping the previous
+    /// model, if the sweep covered less than 1°C.
+    pub fn capture_temperature_sweep<D: DelayUs<u32>>(
+        &mut self,
+        delay: &mut D,
+        readings: u16,
+        interval: MillisDurationU32,
+    ) -> Result<Option<GyroTemperatureModel>, E> {
+        let mut sweep = TemperatureSweep::new();
+        for i in 0..readings {
+            let mean = self.sample_mean(SWEEP_AVERAGE, delay)?;
+            sweep.update(mean.temperature, mean.gyro);
+            if i + 1 < readings {
+                delay.delay_us(interval.to_micros());
+            }
+        }
+        let model = sweep.fit();
+        match model {
+            Some(model) => {
+                debug!("gyro slope {} {} {} per °C", model.slope.0, model.slope.1, model.slope.2);
+                self.calibration.gyro_temperature_model = Some(model);
+                self.calibration.gyro_bias = None;
+            }
+            None => warn!("temperature sweep covered only {}°C", sweep.span()),
         }
+        Ok(model)
+    }
 
-        fn set_low(&mut self) -> Result<(), &'static str> {
-            Ok(())
+    /// Writes the gyro offset registers, see `calibration::gyro_offset_counts`
+    pub fn set_gyro_offsets(&mut self, offsets: [i16; 3]) -> Result<(), E> {
+        let registers = [
+            (Register::GyroXOffsetHigh, Register::GyroXOffsetLow),
+            (Register::GyroYOffsetHigh, Register::GyroYOffsetLow),
+            (Register::GyroZOffsetHigh, Register::GyroZOffsetLow),
+        ];
+        for (&(high, low), offset) in registers.iter().zip(offsets.iter()) {
+            let [msb, lsb] = offset.to_be_bytes();
+            self.bus.write(high, msb)?;
+            self.bus.write(low, lsb)?;
         }
+        Ok(())
     }
 
-    struct Nodelay {}
+    /// Averages `samples` accelerometer readings at rest and reports which sensor
+    /// axis points up, e.g. to pick the board orientation of user-assembled hardware
+    pub fn detect_gravity_axis<D: DelayUs<u32>>(
+        &mut self,
+        delay: &mut D,
+        samples: u16,
+    ) -> Result<Option<GravityAxis>, E> {
+        Ok(GravityAxis::from_gravity(self.sample_mean(samples, delay)?.acceleration))
+    }
 
-    impl DelayMs<u8> for Nodelay {
-        fn delay_ms(&mut self, _ms: u8) {}
+    /// Same as `measure_level`, averaging over `duration` at the current sample rate
+    pub fn measure_level_for<D: DelayUs<u32>, const NOM: u32, const DENOM: u32>(
+        &mut self,
+        delay: &mut D,
+        duration: Duration<u32, NOM, DENOM>,
+    ) -> Result<LevelTrim, E> {
+        let samples = self.samples_in(duration);
+        self.measure_level(delay, samples)
     }
 
-    impl DelayUs<u8> for Nodelay {
-        fn delay_us(&mut self, _us: u8) {}
+    /// Number of samples the device outputs in `duration`, at least 1
+    pub fn samples_in<const NOM: u32, const DENOM: u32>(
+        &self,
+        duration: Duration<u32, NOM, DENOM>,
+    ) -> u16 {
+        let samples = duration.to_millis() as u64 * self.sample_rate() as u64 / 1_000;
+        samples.clamp(1, u16::MAX as u64) as u16
     }
 
-    #[test]
-    fn test_functional() {
-        extern crate std;
+    /// Sums `samples` (at least 1) `read_all` bursts spaced by the output data rate,
+    /// in accelerometer, temperature, gyro order
+    fn sum_samples<D: DelayUs<u32>>(&mut self, samples: u16, delay: &mut D) -> Result<[i32; 7], E> {
+        let period = 1_000_000 / self.sample_rate() as u32;
+        let mut sum = [0i32; 7];
+        for i in 0..samples.max(1) {
+            let (acceleration, temperature, gyro) = self.read_all()?;
+            let fields = [
+                acceleration.0,
+                acceleration.1,
+                acceleration.2,
+                temperature.0,
+                gyro.0,
+                gyro.1,
+                gyro.2,
+            ];
+            for (sum, value) in sum.iter_mut().zip(fields.iter()) {
+                *sum += *value as i32;
+            }
+            if i + 1 < samples {
+                delay.delay_us(period);
+            }
+        }
+        Ok(sum)
+    }
 
-        use crate::bus::SpiBus;
-        use crate::registers::{AccelerometerRange, GyroRange};
-        use crate::MPU6000;
+    /// Mean of `samples` back-to-back readings spaced by the output data rate,
+    /// for low noise static measurements
+    pub fn read_averaged<D: DelayUs<u32>>(
+        &mut self,
+        samples: u16,
+        delay: &mut D,
+    ) -> Result<ImuSample, E> {
+        let sum = self.sum_samples(samples, delay)?;
+        let n = samples.max(1) as f32;
+        let mean = |sum: i32| libm::roundf(sum as f32 / n) as i16;
+        Ok(ImuSample {
+            acceleration: Acceleration(mean(sum[0]), mean(sum[1]), mean(sum[2])),
+            temperature: Temperature(mean(sum[3])),
+            gyro: Gyro(mean(sum[4]), mean(sum[5]), mean(sum[6])),
+        })
+    }
 
-        let spi_bus = SpiBus::new(StubSPI {}, StubOutputPin {}, Nodelay {});
+    /// Mean of `samples` `read_all` bursts taken as fast as the output data rate
+    /// allows, scaled without rounding. Orientation is applied, level trim, gyro bias
+    /// and software filters are not, so it suits calibration and bench measurements.
+    pub fn sample_mean<D: DelayUs<u32>>(
+        &mut self,
+        samples: u16,
+        delay: &mut D,
+    ) -> Result<SampleMean, E> {
+        let sum = self.sum_samples(samples, delay)?;
+        Ok(self.scale_sum(sum, samples))
+    }
+
+    /// Scaled means of sums from `sum_samples`
+    fn scale_sum(&self, sum: [i32; 7], samples: u16) -> SampleMean {
+        let n = samples.max(1) as f32;
+        let mean = |sum: i32| sum as f32 / n;
+        let accelerometer_scale = self.accelerometer_scale_factor();
+        let gyro_scale = self.gyro_range().scale_factor();
+        let variant = self.variant;
+        SampleMean {
+            acceleration: (
+                mean(sum[0]) / accelerometer_scale,
+                mean(sum[1]) / accelerometer_scale,
+                mean(sum[2]) / accelerometer_scale,
+            ),
+            temperature: mean(sum[3]) / variant.temperature_sensitivity()
+                + variant.temperature_offset(),
+            gyro: (mean(sum[4]) / gyro_scale, mean(sum[5]) / gyro_scale, mean(sum[6]) / gyro_scale),
+        }
+    }
+
+    /// Same as `read_averaged`, averaging over `duration` at the current sample rate
+    pub fn read_averaged_for<D: DelayUs<u32>, const NOM: u32, const DENOM: u32>(
+        &mut self,
+        duration: Duration<u32, NOM, DENOM>,
+        delay: &mut D,
+    ) -> Result<ImuSample, E> {
+        let samples = self.samples_in(duration);
+        self.read_averaged(samples, delay)
+    }
+
+    /// Board alignment applied to every accelerometer and gyro read, clip tracking
+    /// and auto ranging still see the sensor axes
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.orientation.rotation = rotation
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.orientation.rotation
+    }
+
+    /// Sign flips of the accelerometer sensor axes, applied before the rotation
+    pub fn set_accelerometer_inversion(&mut self, inversion: Inversion) {
+        self.orientation.accelerometer_inversion = inversion
+    }
+
+    /// Sign flips of the gyro sensor axes, applied before the rotation
+    pub fn set_gyro_inversion(&mut self, inversion: Inversion) {
+        self.orientation.gyro_inversion = inversion
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        self.orientation = orientation
+    }
+
+    /// Orientation correction applied to `read_accel` and `read_gyro`, `None` disables it
+    pub fn set_level_trim(&mut self, trim: Option<LevelTrim>) {
+        self.calibration.level_trim = trim
+    }
+
+    /// Gyro offset in °/s subtracted from `read_gyro`, `None` disables it. Replaces a
+    /// gyro temperature model.
+    pub fn set_gyro_bias(&mut self, bias: Option<(f32, f32, f32)>) {
+        self.calibration.gyro_bias = bias;
+        self.calibration.gyro_temperature_model = None;
+    }
+
+    /// Zero rate offset subtracted by `read_gyro`: the temperature model evaluated at
+    /// the last temperature read, or the fixed bias. Whichever of
+    /// `capture_temperature_sweep` and `calibrate`/`set_gyro_bias` ran last is in use,
+    /// each clears the other's result.
+    fn gyro_bias(&self) -> Option<(f32, f32, f32)> {
+        let model = self.calibration.gyro_temperature_model;
+        match (model, self.last_temperature) {
+            (Some(model), Some(temperature)) => Some(model.bias(temperature.celsius(self.variant))),
+            _ => self.calibration.gyro_bias,
+        }
+    }
+
+    pub fn calibration(&self) -> CalibrationData {
+        self.calibration
+    }
+
+    pub fn set_calibration(&mut self, calibration: CalibrationData) {
+        self.calibration = calibration
+    }
+
+    pub fn read_temperature(&mut self) -> Result<Temperature, E> {
+        let mut buffer = [0u8; 2];
+        self.bus.reads(Register::TemperatureHigh, &mut buffer)?;
+        let temperature = buffer[..].into();
+        self.monitor_temperature(temperature);
+        Ok(temperature)
+    }
+
+    /// Temperature in °C using the conversion of the configured `Variant`
+    pub fn read_celsius(&mut self) -> Result<f32, E> {
+        Ok(self.read_temperature()?.celsius(self.variant))
+    }
+
+    pub fn read_all(&mut self) -> Result<(Acceleration, Temperature, Gyro), E> {
+        self.read_data(false)
+    }
+
+    /// Burst reads the sensor data, `fresh` if data ready was seen set before it
+    fn read_data(&mut self, fresh: bool) -> Result<(Acceleration, Temperature, Gyro), E> {
+        let mut buffer = [0u8; 14];
+        self.bus.reads(Register::AccelerometerXHigh, &mut buffer)?;
+        self.decode_all(&buffer, fresh)
+    }
+
+    /// Reads INT_STATUS together with the sensor data in one burst, returns the
+    /// sample only if data ready was set
+    pub fn read_if_ready(&mut self) -> Result<Option<ImuSample>, E> {
+        let mut buffer = [0u8; 15];
+        self.bus.reads(Register::IntStatus, &mut buffer)?;
+        if !InterruptStatus(buffer[0]).data_ready() {
+            return Ok(None);
+        }
+        Ok(Some(self.decode_all(&buffer[1..], true)?.into()))
+    }
+
+    /// Checks INT_STATUS once without delaying and reads a sample only if data ready
+    /// was set, for superloops polling every iteration. Transfers a single byte
+    /// while no new data is available, unlike `read_if_ready`.
+    pub fn poll_data_ready(&mut self) -> Result<Option<ImuSample>, E> {
+        if !self.read_interrupt_status()?.data_ready() {
+            return Ok(None);
+        }
+        Ok(Some(self.read_data(true)?.into()))
+    }
+
+    /// Reads INT_STATUS together with the sensor data in one burst and marks the
+    /// sample stale if data ready was clear and it equals the previous one, so loops
+    /// running faster than the output data rate can skip repeated measurements
+    pub fn read_sample_freshness(&mut self) -> Result<(ImuSample, Freshness), E> {
+        let mut buffer = [0u8; 15];
+        self.bus.reads(Register::IntStatus, &mut buffer)?;
+        let data_ready = InterruptStatus(buffer[0]).data_ready();
+        let sample: ImuSample = self.decode_all(&buffer[1..], data_ready)?.into();
+        let repeated = self.previous_sample.replace(sample) == Some(sample);
+        let freshness = if data_ready || !repeated { Freshness::Fresh } else { Freshness::Stale };
+        Ok((sample, freshness))
+    }
+
+    /// Decodes a burst, only `fresh` bursts are checked for lock-up as polling faster
+    /// than the output data rate reads the same sample again
+    fn decode_all(
+        &mut self,
+        buffer: &[u8],
+        fresh: bool,
+    ) -> Result<(Acceleration, Temperature, Gyro), E> {
+        if let Some(detector) = self.lock_up_detector.as_mut().filter(|_| fresh) {
+            let mut burst = [0u8; 14];
+            burst.copy_from_slice(&buffer[..14]);
+            if detector.update(&burst) {
+                error!("sensor data stuck, device locked up");
+                self.lock_up = true;
+            }
+        }
+        let (acceleration, gyro): (Acceleration, Gyro) = (buffer[..6].into(), buffer[8..14].into());
+        self.track_acceleration(&acceleration)?;
+        self.track_gyro(&gyro)?;
+        let temperature = buffer[6..8].into();
+        self.monitor_temperature(temperature);
+        let orientation = &self.orientation;
+        Ok((orientation.acceleration(acceleration), temperature, orientation.gyro(gyro)))
+    }
+
+    pub fn read_sample(&mut self) -> Result<ImuSample, E> {
+        Ok(self.read_all()?.into())
+    }
+
+    pub fn accelerometer_range(&self) -> AccelerometerRange {
+        P::ACCELEROMETER_RANGE.unwrap_or(self.accelerometer_range)
+    }
+
+    /// LSB per g of the range, halved on revisions C4 and C5
+    fn accelerometer_scale_factor(&self) -> f32 {
+        let scale_factor = self.accelerometer_range().scale_factor();
+        if self.product_id.accelerometer_half_scale() {
+            scale_factor / 2.0
+        } else {
+            scale_factor
+        }
+    }
+
+    pub fn gyro_range(&self) -> GyroRange {
+        P::GYRO_RANGE.unwrap_or(self.gyro_range)
+    }
+
+    /// Full-scale sample counts accumulated by the read methods since
+    /// creation or the last `reset_clip_stats`
+    pub fn clip_stats(&self) -> ClipStats {
+        self.clip_stats
+    }
+
+    pub fn reset_clip_stats(&mut self) {
+        self.clip_stats = ClipStats::default()
+    }
+
+    /// Checks temperature readings against `thresholds`, `None` disables the check
+    pub fn set_temperature_thresholds(&mut self, thresholds: Option<TemperatureThresholds>) {
+        self.temperature_monitor = thresholds.map(TemperatureMonitor::new);
+        self.temperature_alarm = None;
+    }
+
+    /// Latest threshold crossing seen by `read_temperature` or `read_all`, if not taken yet
+    pub fn take_temperature_alarm(&mut self) -> Option<TemperatureAlarm> {
+        self.temperature_alarm.take()
+    }
+
+    /// Watches burst reads for `limit` repeats of the same raw data in a row,
+    /// `None` disables the check. Only reads that saw data ready set count, those of
+    /// `read_if_ready`, `poll_data_ready` and `read_sample_freshness`, as `read_all`
+    /// polled faster than the output data rate returns the same sample again.
+    pub fn set_lock_up_detection(&mut self, limit: Option<u16>) {
+        self.lock_up_detector = limit.map(LockUpDetector::new);
+        self.lock_up = false;
+    }
+
+    /// `ImuEvent::LockUp` if the burst reads saw a lock-up since the last call
+    pub fn take_lock_up(&mut self) -> Option<ImuEvent> {
+        if !core::mem::take(&mut self.lock_up) {
+            return None;
+        }
+        Some(ImuEvent::LockUp)
+    }
+
+    fn monitor_temperature(&mut self, temperature: Temperature) {
+        self.last_temperature = Some(temperature);
+        let variant = self.variant;
+        let alarm = self.temperature_monitor.as_mut().and_then(|m| m.update(temperature, variant));
+        if let Some(alarm) = alarm {
+            warn!("temperature alarm {}", temperature.centi_celcius_for(variant));
+            self.temperature_alarm = Some(alarm);
+        }
+    }
+
+    fn track_acceleration(&mut self, acceleration: &Acceleration) -> Result<(), E> {
+        self.clip_stats.record_acceleration(acceleration);
+        let config = match self.auto_range {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+        let range = match self.accelerometer_tracker.update(&config, acceleration.to_array()) {
+            Some(Step::Up) => self.accelerometer_range.larger(),
+            Some(Step::Down) => self.accelerometer_range.smaller(),
+            None => None,
+        };
+        match range {
+            Some(range) => self.write_accelerometer_range(range),
+            None => Ok(()),
+        }
+    }
+
+    fn track_gyro(&mut self, gyro: &Gyro) -> Result<(), E> {
+        self.clip_stats.record_gyro(gyro);
+        let config = match self.auto_range {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+        let range = match self.gyro_tracker.update(&config, gyro.to_array()) {
+            Some(Step::Up) => self.gyro_range.larger(),
+            Some(Step::Down) => self.gyro_range.smaller(),
+            None => None,
+        };
+        match range {
+            Some(range) => self.write_gyro_range(range),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Setters for configuration a compile-time profile would otherwise fix
+impl<E, BUS: RegAccess<Error = E>> MPU6000<BUS> {
+    pub fn new(bus: BUS) -> Self {
+        Self::with_profile(bus)
+    }
+
+    pub fn set_dlpf(&mut self, value: u8) -> Result<(), E> {
+        self.write_dlpf(value)
+    }
+
+    /// set DLPF before set sample rate, rates the divider cannot reach are rejected
+    /// with `Error::InvalidArgument`
+    pub fn set_sample_rate(&mut self, rate: u16) -> Result<(), Error<E>> {
+        let divider =
+            encoding::sample_rate_divider(self.dlpf, rate).ok_or(Error::InvalidArgument)?;
+        debug!("set sample rate {}Hz, divider {}", rate, divider);
+        self.write_sample_rate_divider(divider).map_err(Error::Bus)
+    }
+
+    /// Writes every field of `config` to the device
+    pub fn configure(&mut self, config: &Mpu6000Config) -> Result<(), E> {
+        self.write_dlpf(config.dlpf)?;
+        self.write_sample_rate_divider(config.sample_rate_divider)?;
+        self.write_accelerometer_range(config.accelerometer_range)?;
+        self.write_gyro_range(config.gyro_range)
+    }
+
+    pub fn set_accelerometer_range(&mut self, range: AccelerometerRange) -> Result<(), E> {
+        self.write_accelerometer_range(range)
+    }
+
+    pub fn set_gyro_range(&mut self, range: GyroRange) -> Result<(), E> {
+        self.write_gyro_range(range)
+    }
+
+    /// Enables automatic range switching on sustained clipping, `None` disables it.
+    /// Scaled reads always use the range the sample was taken with.
+    pub fn set_auto_range(&mut self, auto_range: Option<AutoRange>) {
+        self.auto_range = auto_range;
+        self.accelerometer_tracker = RangeTracker::default();
+        self.gyro_tracker = RangeTracker::default();
+    }
+}
+
+impl<BUS, P> MPU6000<BUS, P> {
+    pub fn free(self) -> BUS {
+        self.bus
+    }
+
+    pub fn bus(&self) -> &BUS {
+        &self.bus
+    }
+
+    /// Direct bus access that bypasses the driver, register state tracked by the
+    /// driver is not updated by writes made through it
+    pub fn bus_mut(&mut self) -> &mut BUS {
+        &mut self.bus
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use embedded_hal::blocking::delay::{DelayMs, DelayUs};
+    use embedded_hal::blocking::spi::{Transfer, Write};
+    use embedded_hal::digital::v2::OutputPin;
+
+    struct StubSPI {}
+
+    impl Write<u8> for StubSPI {
+        type Error = &'static str;
+        fn write(&mut self, _bytes: &[u8]) -> Result<(), &'static str> {
+            Ok(())
+        }
+    }
+
+    impl Transfer<u8> for StubSPI {
+        type Error = &'static str;
+        fn transfer<'w>(&mut self, bytes: &'w mut [u8]) -> Result<&'w [u8], &'static str> {
+            for b in bytes.iter_mut() {
+                *b = 100;
+            }
+            Ok(bytes)
+        }
+    }
+
+    struct StubOutputPin {}
+    impl OutputPin for StubOutputPin {
+        type Error = &'static str;
+        fn set_high(&mut self) -> Result<(), &'static str> {
+            Ok(())
+        }
+
+        fn set_low(&mut self) -> Result<(), &'static str> {
+            Ok(())
+        }
+    }
+
+    struct Nodelay {}
+
+    impl DelayMs<u32> for Nodelay {
+        fn delay_ms(&mut self, _ms: u32) {}
+    }
+
+    impl DelayUs<u8> for Nodelay {
+        fn delay_us(&mut self, _us: u8) {}
+    }
+
+    impl DelayUs<u32> for Nodelay {
+        fn delay_us(&mut self, _us: u32) {}
+    }
+
+    /// Sums the requested delays in ms
+    struct Elapsed(u32);
+
+    impl DelayMs<u32> for Elapsed {
+        fn delay_ms(&mut self, ms: u32) {
+            self.0 += ms;
+        }
+    }
+
+    /// Expires every `period` polls
+    struct Ticks(u32, u32);
+
+    impl embedded_hal::timer::CountDown for Ticks {
+        type Time = u32;
+
+        fn start<T: Into<u32>>(&mut self, period: T) {
+            *self = Ticks(period.into(), 0);
+        }
+
+        fn wait(&mut self) -> nb::Result<(), void::Void> {
+            self.1 += 1;
+            if self.1 < self.0 {
+                return Err(nb::Error::WouldBlock);
+            }
+            self.1 = 0;
+            Ok(())
+        }
+    }
+
+    /// Reads the same byte from every register
+    struct ConstBus(u8);
+
+    impl crate::bus::RegAccess for ConstBus {
+        type Error = ();
+
+        fn write(&mut self, _reg: crate::registers::Register, _value: u8) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn read(&mut self, _reg: crate::registers::Register) -> Result<u8, ()> {
+            Ok(self.0)
+        }
+
+        fn reads(&mut self, _reg: crate::registers::Register, output: &mut [u8]) -> Result<(), ()> {
+            output.iter_mut().for_each(|b| *b = self.0);
+            Ok(())
+        }
+    }
+
+    /// Awake, with sensor data changing on every burst read
+    struct TickingBus(u8);
+
+    impl crate::bus::RegAccess for TickingBus {
+        type Error = ();
+
+        fn write(&mut self, _reg: crate::registers::Register, _value: u8) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn read(&mut self, _reg: crate::registers::Register) -> Result<u8, ()> {
+            Ok(0)
+        }
+
+        fn reads(&mut self, _reg: crate::registers::Register, output: &mut [u8]) -> Result<(), ()> {
+            self.0 = self.0.wrapping_add(1);
+            output.iter_mut().for_each(|b| *b = self.0);
+            Ok(())
+        }
+    }
+
+    /// Register map in memory, recording every write
+    #[derive(Default)]
+    struct RegisterFile {
+        registers: std::collections::BTreeMap<u8, u8>,
+        writes: std::vec::Vec<(crate::registers::Register, u8)>,
+    }
+
+    impl RegisterFile {
+        fn get(&self, reg: crate::registers::Register) -> u8 {
+            self.registers.get(&(reg as u8)).copied().unwrap_or(0)
+        }
+
+        fn set(&mut self, reg: crate::registers::Register, value: u8) {
+            self.registers.insert(reg as u8, value);
+        }
+    }
+
+    impl crate::bus::RegAccess for RegisterFile {
+        type Error = ();
+
+        fn write(&mut self, reg: crate::registers::Register, value: u8) -> Result<(), ()> {
+            self.set(reg, value);
+            self.writes.push((reg, value));
+            Ok(())
+        }
+
+        fn read(&mut self, reg: crate::registers::Register) -> Result<u8, ()> {
+            Ok(self.get(reg))
+        }
+
+        /// Consecutive registers, the FIFO data register is not special cased
+        fn reads(&mut self, reg: crate::registers::Register, output: &mut [u8]) -> Result<(), ()> {
+            for (address, b) in (reg as u8..).zip(output.iter_mut()) {
+                *b = self.registers.get(&address).copied().unwrap_or(0);
+            }
+            Ok(())
+        }
+    }
+
+    fn stub_mpu6000() -> crate::MPU6000<crate::bus::SpiBus<StubSPI, StubOutputPin, Nodelay>> {
+        crate::MPU6000::new(crate::bus::SpiBus::new(StubSPI {}, StubOutputPin {}, Nodelay {}))
+    }
+
+    #[test]
+    fn test_functional() {
+        extern crate std;
+
+        use crate::bus::SpiBus;
+        use crate::registers::{AccelerometerRange, GyroRange};
+        use crate::MPU6000;
+
+        let spi_bus = SpiBus::new(StubSPI {}, StubOutputPin {}, Nodelay {});
         let mut mpu6000 = MPU6000::new(spi_bus);
         let mut delay = Nodelay {};
         mpu6000.reset(&mut delay).ok();
@@ -256,4 +1790,560 @@ mod test {
         mpu6000.set_gyro_range(GyroRange::DPS2000).ok();
         mpu6000.read_all().ok();
     }
+
+    #[test]
+    fn test_read_scaled() {
+        let mut mpu6000 = stub_mpu6000();
+        let g = 25700.0 / 16384.0;
+        assert_eq!(mpu6000.read_accel().ok(), Some((g, g, g)));
+        let rate = 25700.0 / 131.0;
+        assert_eq!(mpu6000.read_gyro().ok(), Some((rate, rate, rate)));
+    }
+
+    #[test]
+    fn test_gyro_lowpass() {
+        let mut mpu6000 = stub_mpu6000();
+        let rate = 25700.0 / 131.0;
+        mpu6000.enable_gyro_lowpass(100.0, 2);
+        // the filter starts from rest and settles on the constant stub rate
+        let (x, _, _) = mpu6000.read_gyro().ok().unwrap();
+        assert!(x > 0.0 && x < rate / 10.0);
+        for _ in 0..1000 {
+            mpu6000.read_gyro().ok();
+        }
+        let (x, y, z) = mpu6000.read_gyro().ok().unwrap();
+        assert!((x - rate).abs() < 1e-2 && x == y && y == z);
+        mpu6000.disable_gyro_lowpass();
+        assert_eq!(mpu6000.read_gyro().ok(), Some((rate, rate, rate)));
+    }
+
+    #[test]
+    fn test_measure_level() {
+        let mut mpu6000 = stub_mpu6000();
+        let trim = mpu6000.measure_level(&mut Nodelay {}, 10).ok();
+        assert!(trim.is_some());
+        mpu6000.set_level_trim(trim);
+        let (x, y, z) = mpu6000.read_accel().ok().unwrap();
+        assert!(x.abs() < 1e-3 && y.abs() < 1e-3 && z > 0.0);
+    }
+
+    #[test]
+    fn test_split_sensors() {
+        let mpu6000 = core::cell::RefCell::new(stub_mpu6000());
+        let (accel, gyro, thermometer) = crate::MPU6000::split_sensors(&mpu6000);
+        assert!(accel.read().is_ok());
+        assert!(matches!(gyro.read_raw(), Ok(crate::Gyro(25700, 25700, 25700))));
+        assert!(thermometer.read().is_ok());
+    }
+
+    #[test]
+    fn test_select_best_clock() {
+        let mut mpu6000 = stub_mpu6000();
+        // stub reads back 100, CLKSEL 4
+        assert!(matches!(mpu6000.select_best_clock(), Ok(false)));
+    }
+
+    #[test]
+    fn test_read_averaged() {
+        let mut mpu6000 = stub_mpu6000();
+        let sample = mpu6000.read_averaged(4, &mut Nodelay {}).ok().unwrap();
+        assert_eq!(sample.gyro, crate::Gyro(25700, 25700, 25700));
+    }
+
+    #[test]
+    fn test_periodic_sampler() {
+        let mut sampler = crate::PeriodicSampler::new(stub_mpu6000(), Ticks(0, 0), 2u32);
+        assert!(matches!(sampler.poll(), Ok(None)));
+        assert!(matches!(sampler.poll(), Ok(Some(_))));
+    }
+
+    #[test]
+    fn test_samples_in() {
+        let mpu6000 = stub_mpu6000();
+        let samples = mpu6000.samples_in(fugit::MillisDurationU32::millis(250));
+        assert_eq!(samples, mpu6000.sample_rate() / 4);
+    }
+
+    #[test]
+    fn test_suspend_resume() {
+        use crate::registers::Register::*;
+
+        let configuration = [
+            (PowerManagement1, 0x01),
+            (PowerManagement2, 0x00),
+            (SampleRateDivider, 7),
+            (Configuration, 3),
+            (GyroConfig, 0x18),
+            (AccelerometerConfig, 0x10),
+            (FifoEnable, 0x08),
+            (IntPinConfig, 0x30),
+            (InterruptEnable, 0x01),
+            (UserControl, 0x40),
+        ];
+        let mut bus = RegisterFile::default();
+        configuration.iter().for_each(|&(register, value)| bus.set(register, value));
+        let mut mpu6000 = crate::MPU6000::new(bus);
+        assert!(mpu6000.suspend().is_ok());
+        assert_eq!(mpu6000.bus().writes, [(PowerManagement1, 0x41)]);
+        // power gated while suspended
+        *mpu6000.bus_mut() = RegisterFile::default();
+        assert!(mpu6000.resume(&mut Nodelay {}).is_ok());
+        assert_eq!(mpu6000.free().writes, configuration);
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        use crate::MPU6000;
+
+        let mut mpu6000 = stub_mpu6000();
+        mpu6000.set_gyro_bias(Some((0.5, 0.0, -0.5)));
+        let state = mpu6000.state();
+        let mpu6000: MPU6000<_> = MPU6000::from_state(mpu6000.free(), state);
+        assert!(mpu6000.state() == state);
+    }
+
+    #[test]
+    fn test_accelerometer_high_pass() {
+        let mut mpu6000 = stub_mpu6000();
+        let filter = crate::registers::AccelerometerHighPass::Hz5;
+        assert!(mpu6000.set_accelerometer_high_pass(filter).is_ok());
+        mpu6000.set_variant(crate::Variant::Mpu9250);
+        let result = mpu6000.set_accelerometer_high_pass(filter);
+        assert!(matches!(result, Err(crate::Error::Unsupported)));
+    }
+
+    #[test]
+    fn test_gyro_bandwidth() {
+        let mut mpu6000 = stub_mpu6000();
+        let bandwidth = crate::registers::GyroBandwidth::Hz8800;
+        assert!(matches!(mpu6000.set_gyro_bandwidth(bandwidth), Err(crate::Error::Unsupported)));
+        mpu6000.set_variant(crate::Variant::Mpu6500);
+        assert!(mpu6000.set_gyro_bandwidth(bandwidth).is_ok());
+        assert_eq!(mpu6000.sample_rate(), 32_000);
+    }
+
+    #[test]
+    fn test_accelerometer_dlpf() {
+        let mut mpu6000 = stub_mpu6000();
+        let dlpf = crate::registers::AccelerometerDlpf::Hz45;
+        assert!(matches!(mpu6000.set_accelerometer_dlpf(dlpf), Err(crate::Error::Unsupported)));
+        mpu6000.set_variant(crate::Variant::Mpu6500);
+        assert!(mpu6000.set_accelerometer_dlpf(dlpf).is_ok());
+    }
+
+    #[test]
+    fn test_read_if_ready() {
+        let mut mpu6000 = stub_mpu6000();
+        // stub status 100 has data ready clear
+        assert!(matches!(mpu6000.read_if_ready(), Ok(None)));
+    }
+
+    #[test]
+    fn test_read_fifo() {
+        let mut mpu6000 = stub_mpu6000();
+        mpu6000.enable_fifo(crate::FifoEnable { acceleration: true, ..Default::default() }).ok();
+        let mut buffer = [0u8; 32];
+        let len = mpu6000.read_fifo(&mut buffer).unwrap_or(0);
+        assert_eq!(crate::FifoFrames::new(&mpu6000.fifo_enable(), &buffer[..len]).count(), 5);
+    }
+
+    #[test]
+    fn test_wait_for_fifo_level() {
+        let mut mpu6000 = stub_mpu6000();
+        mpu6000.enable_fifo(crate::FifoEnable { acceleration: true, ..Default::default() }).ok();
+        let timeout = fugit::MillisDurationU32::millis(10);
+        // stub FIFO count 25700 holds 4283 six byte frames
+        assert!(matches!(mpu6000.wait_for_fifo_level(2, &mut Nodelay {}, timeout), Ok(4283)));
+    }
+
+    #[test]
+    fn test_auto_sleep() {
+        use crate::auto_sleep::{AutoSleep, AutoSleepConfig};
+
+        let mut mpu6000 = stub_mpu6000();
+        let mut delay = Nodelay {};
+        let mut auto_sleep = AutoSleep::<4>::new(AutoSleepConfig {
+            idle_time: fugit::MillisDurationU32::millis(1),
+            max_variance: 0.01,
+            threshold_mg: 40,
+            wake_rate: crate::registers::WakeRate::Hz5,
+        });
+        for _ in 0..32 {
+            auto_sleep.update(&mut mpu6000, (0.0, 0.0, 1.0), &mut delay).ok();
+        }
+        assert!(auto_sleep.is_sleeping());
+        auto_sleep.on_motion(&mut mpu6000, &mut delay).ok();
+        assert!(!auto_sleep.is_sleeping());
+    }
+
+    #[test]
+    fn test_read_gyro_as() {
+        let mut mpu6000 = stub_mpu6000();
+        let rate = 25700.0 / 131.0;
+        assert_eq!(mpu6000.read_gyro_as::<f64>().ok(), Some((rate, rate, rate)));
+    }
+
+    #[test]
+    fn test_set_sample_rate() {
+        let mut mpu6000 = stub_mpu6000();
+        assert!(matches!(mpu6000.set_sample_rate(0), Err(crate::Error::InvalidArgument)));
+        assert!(matches!(mpu6000.set_sample_rate(20), Err(crate::Error::InvalidArgument)));
+        assert!(mpu6000.set_sample_rate(1000).is_ok());
+        assert_eq!(mpu6000.sample_rate(), 1000);
+    }
+
+    #[test]
+    fn test_drift_apply() {
+        let mut mpu6000 = stub_mpu6000();
+        let mut drift = crate::drift::GyroDriftEstimator::<4>::new(0.5, 0.01);
+        for _ in 0..8 {
+            drift.update((0.5, -0.25, 0.0));
+        }
+        assert!(drift.confidence() > 0.9);
+        drift.apply_to(&mut mpu6000);
+        assert_eq!(mpu6000.calibration().gyro_bias, Some(drift.bias()));
+    }
+
+    #[test]
+    fn test_auto_recalibration() {
+        use crate::drift::{AutoRecalibration, RecalibrationConfig};
+
+        let mut mpu6000 = stub_mpu6000();
+        let mut recalibration = AutoRecalibration::<4>::new(RecalibrationConfig {
+            hold_time: fugit::MillisDurationU32::millis(1),
+            enter_variance: 0.01,
+            exit_variance: 0.04,
+            allow: Some(|_| true),
+        });
+        let applied = (0..32).filter_map(|_| recalibration.update(&mut mpu6000, (0.5, 0.0, 0.0)));
+        assert_eq!(applied.count(), 1);
+        assert_eq!(mpu6000.calibration().gyro_bias, Some((0.5, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_sample_mean() {
+        let mut mpu6000 = stub_mpu6000();
+        let mean = mpu6000.sample_mean(4, &mut Nodelay {}).ok().unwrap();
+        assert_eq!(mean.acceleration.0, 25700.0 / mpu6000.accelerometer_range().scale_factor());
+        assert_eq!(mean.gyro.2, 25700.0 / mpu6000.gyro_range().scale_factor());
+    }
+
+    #[test]
+    fn test_verify_whoami() {
+        let mut mpu6000 = stub_mpu6000();
+        assert!(matches!(mpu6000.verify_whoami(), Ok(None)));
+        mpu6000.set_accepted_whoami(crate::WhoAmISet::KNOWN.with(100));
+        assert!(matches!(mpu6000.verify_whoami(), Ok(Some(100))));
+        assert!(matches!(mpu6000.verify_whoami_with(|whoami| whoami == 0x68), Ok(None)));
+    }
+
+    #[test]
+    fn test_poll_data_ready() {
+        let mut mpu6000 = stub_mpu6000();
+        // stub status 100 has data ready clear
+        assert!(matches!(mpu6000.poll_data_ready(), Ok(None)));
+    }
+
+    #[test]
+    fn test_sampler_decimation() {
+        let mut sampler = crate::PeriodicSampler::new(stub_mpu6000(), Ticks(0, 0), 2u32);
+        sampler.set_decimation(crate::SensorDecimation::new(2, 1));
+        let frames = (0..8).filter_map(|_| sampler.poll_decimated().ok().flatten());
+        assert_eq!(frames.filter(|frame| frame.acceleration.is_some()).count(), 2);
+    }
+
+    #[test]
+    fn test_unit_imu() {
+        use crate::units::{MetersPerSecond2, Raw, UnitImu, STANDARD_GRAVITY};
+
+        let mut imu: UnitImu<_, _, MetersPerSecond2, Raw> = UnitImu::new(stub_mpu6000());
+        let acceleration = imu.read_accel().ok().unwrap();
+        let (x, _, _) = imu.mpu6000().read_accel().ok().unwrap();
+        assert_eq!(acceleration.x, x * STANDARD_GRAVITY);
+        assert!(matches!(imu.read_gyro(), Ok(crate::Gyro(25700, 25700, 25700))));
+    }
+
+    #[test]
+    fn test_select_clock_source() {
+        use crate::{ClockSource, Error};
+
+        let mut mpu6000 = stub_mpu6000();
+        let mut delay = Nodelay {};
+        // stub data never changes, as with a missing external clock
+        let result = mpu6000.select_clock_source(ClockSource::PLLExternal19_2MHz, &mut delay);
+        assert!(matches!(result, Err(Error::ClockNotPresent)));
+        assert!(mpu6000.select_clock_source(ClockSource::PLLGyroZ, &mut delay).is_ok());
+        mpu6000.set_variant(crate::Variant::Mpu6500);
+        let result = mpu6000.select_clock_source(ClockSource::PLLExternal32_768KHz, &mut delay);
+        assert!(matches!(result, Err(Error::Unsupported)));
+    }
+
+    #[test]
+    fn test_is_ready() {
+        let mut mpu6000 = stub_mpu6000();
+        // stub PWR_MGMT_1 has the sleep bit set
+        let timeout = fugit::MillisDurationU32::millis(5);
+        assert!(matches!(mpu6000.is_ready(&mut Nodelay {}, timeout), Ok(false)));
+        // a timeout under 1ms still checks once
+        let mut mpu6000 = crate::MPU6000::new(TickingBus(0));
+        let timeout = fugit::MicrosDurationU32::micros(500);
+        assert_eq!(mpu6000.is_ready(&mut Nodelay {}, timeout), Ok(true));
+    }
+
+    #[test]
+    fn test_detect_unexpected_reset() {
+        let mut mpu6000 = stub_mpu6000();
+        assert!(matches!(mpu6000.detect_unexpected_reset(), Ok(None)));
+    }
+
+    #[test]
+    fn test_detect_unexpected_reset_mpu6000() {
+        let mut mpu6000 = crate::MPU6000::new(ConstBus(0x40));
+        assert_eq!(mpu6000.detect_unexpected_reset(), Ok(Some(crate::ImuEvent::UnexpectedReset)));
+        let mut mpu6000 = crate::MPU6000::new(ConstBus(0x01));
+        assert_eq!(mpu6000.detect_unexpected_reset(), Ok(None));
+    }
+
+    #[test]
+    fn test_detect_unexpected_reset_mpu6500() {
+        let mut mpu6000 = crate::MPU6000::new(ConstBus(0x01));
+        mpu6000.set_variant(crate::Variant::Mpu6500);
+        assert_eq!(mpu6000.detect_unexpected_reset(), Ok(Some(crate::ImuEvent::UnexpectedReset)));
+        let mut mpu6000 = crate::MPU6000::new(ConstBus(0x40));
+        mpu6000.set_variant(crate::Variant::Mpu9250);
+        assert_eq!(mpu6000.detect_unexpected_reset(), Ok(None));
+    }
+
+    #[test]
+    fn test_check_bus() {
+        let mut mpu6000 = stub_mpu6000();
+        let check = mpu6000.check_bus().ok().unwrap();
+        assert_eq!(check, crate::health::BusCheck::Mismatch { written: 0x55, read: 100 });
+    }
+
+    #[test]
+    fn test_reset_timing() {
+        use crate::registers::Register;
+
+        let mut mpu6000 = crate::MPU6000::new(RegisterFile::default());
+        let timing = crate::ResetTiming {
+            device_reset: fugit::MillisDurationU32::millis(100),
+            ..Default::default()
+        };
+        let mut delay = Elapsed(0);
+        assert!(mpu6000.reset_with_timing(&mut delay, timing).is_ok());
+        assert_eq!(delay.0, 250);
+        let writes = mpu6000.free().writes;
+        assert_eq!(
+            writes,
+            [(Register::PowerManagement1, 0x80), (Register::SignalPathReset, 0b111)]
+        );
+    }
+
+    #[test]
+    fn test_split_reset() {
+        use crate::registers::Register;
+
+        let mut mpu6000 = crate::MPU6000::new(RegisterFile::default());
+        let timing = crate::ResetTiming {
+            device_reset: fugit::MillisDurationU32::millis(100),
+            signal_path_reset: fugit::MillisDurationU32::millis(20),
+        };
+        assert_eq!(mpu6000.begin_reset(timing).map(|wait| wait.to_millis()), Ok(100));
+        assert_eq!(mpu6000.free().writes, [(Register::PowerManagement1, 0x80)]);
+        let mut mpu6000 = crate::MPU6000::new(RegisterFile::default());
+        assert_eq!(mpu6000.finish_reset(timing).map(|wait| wait.to_millis()), Ok(20));
+        assert_eq!(mpu6000.free().writes, [(Register::SignalPathReset, 0b111)]);
+    }
+
+    #[test]
+    fn test_int_pin_settings() {
+        use crate::registers::Register::{IntPinConfig, UserControl};
+
+        let settings = crate::IntPinSettings {
+            mode: crate::InterruptMode::LatchedClearOnStatusRead,
+            i2c_bypass: true,
+            ..Default::default()
+        };
+        assert_eq!(u8::from(settings), 0b10_0010);
+
+        // FIFO_EN and I2C_MST_EN set
+        let mut bus = RegisterFile::default();
+        bus.set(UserControl, 0b110_0000);
+        let mut mpu6000 = crate::MPU6000::new(bus);
+        assert!(mpu6000.set_int_pin_settings(&settings).is_ok());
+        assert_eq!(mpu6000.free().writes, [(UserControl, 0b100_0000), (IntPinConfig, 0b10_0010)]);
+
+        let mut bus = RegisterFile::default();
+        bus.set(UserControl, 0b110_0000);
+        let mut mpu6000 = crate::MPU6000::new(bus);
+        assert!(mpu6000.set_int_pin_config(crate::IntPinConfig::I2cBypassEnable, true).is_ok());
+        assert_eq!(mpu6000.free().writes, [(UserControl, 0b100_0000), (IntPinConfig, 0b10)]);
+
+        let mut bus = RegisterFile::default();
+        bus.set(IntPinConfig, 0b1_0010);
+        let mut mpu6000 = crate::MPU6000::new(bus);
+        assert!(mpu6000.set_i2c_master_enable(true).is_ok());
+        assert_eq!(mpu6000.free().writes, [(IntPinConfig, 0b1_0000), (UserControl, 0b10_0000)]);
+    }
+
+    #[test]
+    fn test_accel_only_mode() {
+        let mut mpu6000 = stub_mpu6000();
+        assert!(matches!(mpu6000.enter_accel_only_mode(3), Err(crate::Error::InvalidArgument)));
+        assert!(mpu6000.enter_accel_only_mode(200).is_ok());
+        assert_eq!(mpu6000.sample_rate(), 200);
+        assert!(mpu6000.exit_accel_only_mode(&mut Nodelay {}).is_ok());
+        // restored from the stub SMPLRT_DIV of 100
+        assert_eq!(mpu6000.sample_rate(), crate::encoding::sample_rate(mpu6000.dlpf, 100));
+    }
+
+    #[test]
+    fn test_gyro_only_mode() {
+        use crate::registers::Register::{PowerManagement1, PowerManagement2, SampleRateDivider};
+
+        let mut bus = RegisterFile::default();
+        bus.set(PowerManagement1, 0x01);
+        bus.set(SampleRateDivider, 7);
+        let mut mpu6000 = crate::MPU6000::new(bus);
+        assert!(mpu6000.enter_gyro_only_mode().is_ok());
+        // STBY_XA, STBY_YA and STBY_ZA
+        assert_eq!(mpu6000.bus().get(PowerManagement2), 0b11_1000);
+        assert_eq!(mpu6000.bus().get(PowerManagement1), 0x01);
+        assert!(mpu6000.exit_gyro_only_mode().is_ok());
+        assert_eq!(mpu6000.bus().get(PowerManagement2), 0);
+        assert_eq!(mpu6000.bus().get(SampleRateDivider), 7);
+        assert_eq!(mpu6000.sample_rate(), 1000);
+    }
+
+    #[test]
+    fn test_revision_scale() {
+        let mut mpu6000 = stub_mpu6000();
+        mpu6000.set_accelerometer_range(crate::registers::AccelerometerRange::G16).ok();
+        let mg = mpu6000.read_acceleration_mg().ok().unwrap();
+        mpu6000.product_id = crate::registers::ProductId::MPU6000_REV_C5;
+        assert_eq!(mpu6000.read_acceleration_mg().ok().unwrap()[0], mg[0] * 2);
+    }
+
+    #[test]
+    fn test_calibrate() {
+        let mut mpu6000 = stub_mpu6000();
+        let mut delay = Nodelay {};
+        let plan = crate::CalibrationPlan { samples: 4, level: true, ..Default::default() };
+        let calibration = mpu6000.calibrate(&mut delay, plan).ok().unwrap();
+        assert!(calibration.gyro_bias.is_some() && calibration.level_trim.is_some());
+        let plan = crate::CalibrationPlan { hardware_offsets: true, ..plan };
+        assert_eq!(mpu6000.calibrate(&mut delay, plan).ok().unwrap().gyro_bias, None);
+    }
+
+    #[test]
+    fn test_calibration_progress() {
+        let mut mpu6000 = stub_mpu6000();
+        let plan = crate::CalibrationPlan {
+            samples: 4,
+            progress: Some(|progress| {
+                assert!(!matches!(progress, crate::CalProgress::Restarted { .. }))
+            }),
+            ..Default::default()
+        };
+        assert!(mpu6000.calibrate(&mut Nodelay {}, plan).is_ok());
+    }
+
+    #[test]
+    fn test_capture_temperature_sweep() {
+        let mut mpu6000 = stub_mpu6000();
+        let interval = fugit::MillisDurationU32::millis(1);
+        // the stub temperature never changes
+        let sweep = mpu6000.capture_temperature_sweep(&mut Nodelay {}, 4, interval);
+        assert_eq!(sweep.ok(), Some(None));
+        assert_eq!(mpu6000.calibration().gyro_temperature_model, None);
+    }
+
+    #[test]
+    fn test_calibrate_replaces_temperature_model() {
+        let mut mpu6000 = stub_mpu6000();
+        let model = crate::GyroTemperatureModel { offset: (1.0, 1.0, 1.0), slope: (0.5, 0.5, 0.5) };
+        let calibration =
+            crate::CalibrationData { gyro_temperature_model: Some(model), ..Default::default() };
+        mpu6000.set_calibration(calibration);
+        mpu6000.read_temperature().ok();
+        let plan = crate::CalibrationPlan { samples: 4, ..Default::default() };
+        assert!(mpu6000.calibrate(&mut Nodelay {}, plan).is_ok());
+        assert_eq!(mpu6000.calibration().gyro_temperature_model, None);
+        // the stub rate never changes, so the captured bias cancels it
+        assert_eq!(mpu6000.read_gyro().ok(), Some((0.0, 0.0, 0.0)));
+
+        mpu6000.set_calibration(calibration);
+        mpu6000.set_gyro_bias(Some((1.0, 2.0, 3.0)));
+        let rate = 25700.0 / 131.0;
+        assert_eq!(mpu6000.read_gyro().ok(), Some((rate - 1.0, rate - 2.0, rate - 3.0)));
+    }
+
+    #[test]
+    fn test_fifo_temperature_interval() {
+        let mut mpu6000 = stub_mpu6000();
+        mpu6000.enable_fifo(crate::FifoEnable { acceleration: true, ..Default::default() }).ok();
+        mpu6000.set_fifo_temperature_interval(Some(fugit::MillisDurationU32::millis(100)));
+        let mut buffer = [0u8; 32];
+        let now = crate::fifo::Timestamp::from_ticks(0);
+        let frame = mpu6000.drain_fifo(&mut buffer, now).ok().and_then(|mut frames| frames.next());
+        assert_eq!(frame.and_then(|frame| frame.temperature), Some(crate::Temperature(25700)));
+    }
+
+    #[test]
+    fn test_sample_freshness() {
+        let mut mpu6000 = stub_mpu6000();
+        let (sample, freshness) = mpu6000.read_sample_freshness().ok().unwrap();
+        assert!(freshness.is_fresh());
+        assert_eq!(mpu6000.read_sample_freshness().ok(), Some((sample, crate::Freshness::Stale)));
+    }
+
+    #[test]
+    fn test_lock_up() {
+        // stub status 100 has data ready clear, re-reads of one sample are not a lock-up
+        let mut mpu6000 = stub_mpu6000();
+        mpu6000.set_lock_up_detection(Some(3));
+        for _ in 0..4 {
+            mpu6000.read_all().ok();
+            mpu6000.read_sample_freshness().ok();
+        }
+        assert_eq!(mpu6000.take_lock_up(), None);
+
+        // data ready set on every read while the data never changes
+        let mut mpu6000 = crate::MPU6000::new(ConstBus(0x01));
+        mpu6000.set_lock_up_detection(Some(3));
+        for _ in 0..4 {
+            mpu6000.read_if_ready().ok();
+        }
+        assert_eq!(mpu6000.take_lock_up(), Some(crate::ImuEvent::LockUp));
+        assert_eq!(mpu6000.take_lock_up(), None);
+    }
+
+    #[test]
+    fn test_fifo_enable() {
+        use crate::FifoEnable;
+
+        let config = FifoEnable::none().with_gyro() | FifoEnable::none().with_accel();
+        assert_eq!(u8::from(config), 0b0111_1000);
+        assert_eq!(FifoEnable::from(0b0111_1000), config);
+        assert_eq!(FifoEnable::all(), FifoEnable::from(0xff).with_temperature());
+        assert_eq!(FifoEnable::none(), FifoEnable::default());
+    }
+
+    #[test]
+    fn test_profile() {
+        use crate::bus::SpiBus;
+        use crate::profile::Fixed;
+        use crate::registers::{AccelerometerRange, GyroRange};
+        use crate::MPU6000;
+
+        let spi_bus = SpiBus::new(StubSPI {}, StubOutputPin {}, Nodelay {});
+        let mut mpu6000 = MPU6000::<_, Fixed<3, 3, 1, 0>>::with_profile(spi_bus);
+        mpu6000.apply_profile().ok();
+        assert_eq!(mpu6000.accelerometer_range(), AccelerometerRange::G16);
+        assert_eq!(mpu6000.gyro_range(), GyroRange::DPS2000);
+        assert_eq!(mpu6000.sample_rate(), 1000);
+        let acceleration = mpu6000.read_accel_as::<f64>();
+        assert!(matches!(acceleration, Ok((x, _, _)) if x == 25700.0 / 2048.0));
+    }
 }
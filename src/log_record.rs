@@ -0,0 +1,193 @@
+//! Compact versioned binary encoding of `ImuSample` for flash or SD logging
+//!
+//! Each record starts with a header byte, version in the high nibble and kind
+//! in the low nibble, followed by:
+//! * full record: accel x/y/z, temperature, gyro x/y/z as little endian i16, 15 bytes total
+//! * delta record: the same 7 fields as i8 differences to the previous record, 8 bytes total
+
+use crate::measurement::{Acceleration, Gyro, ImuSample, Temperature};
+
+pub const VERSION: u8 = 1;
+pub const FULL_RECORD_SIZE: usize = 15;
+pub const DELTA_RECORD_SIZE: usize = 8;
+
+const KIND_FULL: u8 = 0;
+const KIND_DELTA: u8 = 1;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LogError {
+    BufferTooSmall,
+    UnsupportedVersion(u8),
+    InvalidKind(u8),
+    /// Delta record without a preceding full record
+    MissingKeyframe,
+}
+
+fn to_fields(sample: &ImuSample) -> [i16; 7] {
+    let (a, t, g) = (sample.acceleration, sample.temperature, sample.gyro);
+    [a.0, a.1, a.2, t.0, g.0, g.1, g.2]
+}
+
+fn from_fields(f: [i16; 7]) -> ImuSample {
+    ImuSample {
+        acceleration: Acceleration(f[0], f[1], f[2]),
+        temperature: Temperature(f[3]),
+        gyro: Gyro(f[4], f[5], f[6]),
+    }
+}
+
+fn header(kind: u8) -> u8 {
+    VERSION << 4 | kind
+}
+
+/// Stateless full records
+pub struct LogRecord;
+
+impl LogRecord {
+    /// Writes a full record, returns the number of bytes written
+    pub fn encode(sample: &ImuSample, output: &mut [u8]) -> Result<usize, LogError> {
+        let output = output.get_mut(..FULL_RECORD_SIZE).ok_or(LogError::BufferTooSmall)?;
+        output[0] = header(KIND_FULL);
+        for (chunk, field) in output[1..].chunks_mut(2).zip(to_fields(sample).iter()) {
+            chunk.copy_from_slice(&field.to_le_bytes());
+        }
+        Ok(FULL_RECORD_SIZE)
+    }
+
+    /// Reads a full record, returns the sample and the number of bytes consumed
+    pub fn decode(input: &[u8]) -> Result<(ImuSample, usize), LogError> {
+        match LogDecoder::new().decode(input) {
+            Err(LogError::MissingKeyframe) => Err(LogError::InvalidKind(KIND_DELTA)),
+            result => result,
+        }
+    }
+}
+
+/// Emits delta records whenever every field changed by less than ±128 counts,
+/// with a full record at least every `keyframe_interval` records for resync
+#[derive(Copy, Clone, Debug)]
+pub struct LogEncoder {
+    previous: Option<[i16; 7]>,
+    keyframe_interval: u16,
+    since_keyframe: u16,
+}
+
+impl LogEncoder {
+    /// `keyframe_interval` of 0 disables delta records
+    pub fn new(keyframe_interval: u16) -> Self {
+        Self { previous: None, keyframe_interval, since_keyframe: 0 }
+    }
+
+    pub fn encode(&mut self, sample: &ImuSample, output: &mut [u8]) -> Result<usize, LogError> {
+        let fields = to_fields(sample);
+        let mut deltas = [0i8; 7];
+        let delta = match self.previous {
+            Some(previous) if self.since_keyframe < self.keyframe_interval => {
+                fields.iter().zip(previous.iter()).zip(deltas.iter_mut()).all(
+                    |((&field, &previous), delta)| {
+                        let difference = field.wrapping_sub(previous);
+                        *delta = difference as i8;
+                        difference as i8 as i16 == difference
+                    },
+                )
+            }
+            _ => false,
+        };
+        let size = if delta {
+            let output = output.get_mut(..DELTA_RECORD_SIZE).ok_or(LogError::BufferTooSmall)?;
+            output[0] = header(KIND_DELTA);
+            for (byte, delta) in output[1..].iter_mut().zip(deltas.iter()) {
+                *byte = *delta as u8;
+            }
+            self.since_keyframe += 1;
+            DELTA_RECORD_SIZE
+        } else {
+            let size = LogRecord::encode(sample, output)?;
+            self.since_keyframe = 0;
+            size
+        };
+        self.previous = Some(fields);
+        Ok(size)
+    }
+}
+
+/// Decodes a stream of full and delta records
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LogDecoder {
+    previous: Option<[i16; 7]>,
+}
+
+impl LogDecoder {
+    pub fn new() -> Self {
+        Self { previous: None }
+    }
+
+    /// Returns the next sample and the number of bytes consumed
+    pub fn decode(&mut self, input: &[u8]) -> Result<(ImuSample, usize), LogError> {
+        let header = *input.first().ok_or(LogError::BufferTooSmall)?;
+        if header >> 4 != VERSION {
+            return Err(LogError::UnsupportedVersion(header >> 4));
+        }
+        let mut fields = [0i16; 7];
+        let size = match header & 0xf {
+            KIND_FULL => {
+                let input = input.get(1..FULL_RECORD_SIZE).ok_or(LogError::BufferTooSmall)?;
+                for (field, chunk) in fields.iter_mut().zip(input.chunks(2)) {
+                    *field = i16::from_le_bytes([chunk[0], chunk[1]]);
+                }
+                FULL_RECORD_SIZE
+            }
+            KIND_DELTA => {
+                let input = input.get(1..DELTA_RECORD_SIZE).ok_or(LogError::BufferTooSmall)?;
+                let previous = self.previous.ok_or(LogError::MissingKeyframe)?;
+                for ((field, previous), byte) in fields.iter_mut().zip(previous.iter()).zip(input) {
+                    *field = previous.wrapping_add(*byte as i8 as i16);
+                }
+                DELTA_RECORD_SIZE
+            }
+            kind => return Err(LogError::InvalidKind(kind)),
+        };
+        self.previous = Some(fields);
+        Ok((from_fields(fields), size))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_round_trip() {
+        use super::{LogDecoder, LogEncoder, LogError, LogRecord};
+        use crate::measurement::{Acceleration, Gyro, ImuSample, Temperature};
+
+        let sample = |x: i16| ImuSample {
+            acceleration: Acceleration(x, -x, 2048),
+            temperature: Temperature(-521),
+            gyro: Gyro(x / 2, 0, i16::MIN),
+        };
+
+        let mut buffer = [0u8; 64];
+        assert_eq!(LogRecord::encode(&sample(1000), &mut buffer), Ok(15));
+        assert_eq!(LogRecord::decode(&buffer), Ok((sample(1000), 15)));
+        assert_eq!(
+            LogRecord::encode(&sample(1000), &mut buffer[..14]),
+            Err(LogError::BufferTooSmall)
+        );
+
+        let mut encoder = LogEncoder::new(2);
+        let mut offset = 0;
+        for &x in [0, 100, 120, 130, 1000].iter() {
+            offset += encoder.encode(&sample(x), &mut buffer[offset..]).unwrap();
+        }
+        // full, delta, delta, full (keyframe), full (out of delta range)
+        assert_eq!(offset, 15 + 8 + 8 + 15 + 15);
+
+        let mut decoder = LogDecoder::new();
+        let mut offset = 0;
+        for &x in [0, 100, 120, 130, 1000].iter() {
+            let (decoded, size) = decoder.decode(&buffer[offset..]).unwrap();
+            assert_eq!(decoded, sample(x));
+            offset += size;
+        }
+        assert_eq!(LogDecoder::new().decode(&buffer[15..]), Err(LogError::MissingKeyframe));
+    }
+}
@@ -0,0 +1,138 @@
+//! Field layouts of MAVLink RAW_IMU (#27) and SCALED_IMU (#26) messages
+
+use crate::measurement::{ImuSample, Temperature};
+use crate::registers::{AccelerometerRange, GyroRange};
+use crate::variant::Variant;
+
+/// MAVLink reserves 0 for "no temperature", so 0°C is sent as 1 (0.01°C)
+fn centi_celcius(temperature: Temperature, variant: Variant) -> i16 {
+    match temperature.centi_celcius_for(variant) {
+        0 => 1,
+        centi => centi,
+    }
+}
+
+/// RAW_IMU, raw sensor counts, magnetometer fields left zero
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RawImu {
+    pub time_usec: u64,
+    pub xacc: i16,
+    pub yacc: i16,
+    pub zacc: i16,
+    pub xgyro: i16,
+    pub ygyro: i16,
+    pub zgyro: i16,
+    pub xmag: i16,
+    pub ymag: i16,
+    pub zmag: i16,
+    pub id: u8,
+    /// centi-degrees celcius
+    pub temperature: i16,
+}
+
+impl RawImu {
+    /// `variant` selects the temperature conversion
+    pub fn from_sample(sample: &ImuSample, variant: Variant, time_usec: u64) -> Self {
+        let (acceleration, gyro) = (sample.acceleration, sample.gyro);
+        Self {
+            time_usec,
+            xacc: acceleration.0,
+            yacc: acceleration.1,
+            zacc: acceleration.2,
+            xgyro: gyro.0,
+            ygyro: gyro.1,
+            zgyro: gyro.2,
+            temperature: centi_celcius(sample.temperature, variant),
+            ..Default::default()
+        }
+    }
+}
+
+/// SCALED_IMU, acceleration in mG and angular rate in mrad/s, magnetometer fields left zero
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScaledImu {
+    pub time_boot_ms: u32,
+    pub xacc: i16,
+    pub yacc: i16,
+    pub zacc: i16,
+    pub xgyro: i16,
+    pub ygyro: i16,
+    pub zgyro: i16,
+    pub xmag: i16,
+    pub ymag: i16,
+    pub zmag: i16,
+    /// centi-degrees celcius
+    pub temperature: i16,
+}
+
+impl ScaledImu {
+    /// Values beyond the i16 range, e.g. above 1877°/s, saturate. `variant` selects the
+    /// temperature conversion.
+    pub fn from_sample(
+        sample: &ImuSample,
+        accelerometer_range: AccelerometerRange,
+        gyro_range: GyroRange,
+        variant: Variant,
+        time_boot_ms: u32,
+    ) -> Self {
+        let (x, y, z) = sample.acceleration.normalize(accelerometer_range);
+        let milli_g = |g: f32| (g * 1000.0) as i16;
+        let (roll, pitch, yaw) = sample.gyro.normalize(gyro_range);
+        let milli_rad = |dps: f32| (dps.to_radians() * 1000.0) as i16;
+        Self {
+            time_boot_ms,
+            xacc: milli_g(x),
+            yacc: milli_g(y),
+            zacc: milli_g(z),
+            xgyro: milli_rad(roll),
+            ygyro: milli_rad(pitch),
+            zgyro: milli_rad(yaw),
+            temperature: centi_celcius(sample.temperature, variant),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::measurement::{Acceleration, Gyro, ImuSample, Temperature};
+    use crate::registers::{AccelerometerRange, GyroRange};
+    use crate::variant::Variant;
+
+    fn sample(acceleration: i16, temperature: i16, gyro: i16) -> ImuSample {
+        ImuSample {
+            acceleration: Acceleration(acceleration, -acceleration, 0),
+            temperature: Temperature(temperature),
+            gyro: Gyro(gyro, -gyro, 0),
+        }
+    }
+
+    #[test]
+    fn test_scaled_imu() {
+        use super::ScaledImu;
+
+        let (g2, dps250) = (AccelerometerRange::G2, GyroRange::DPS250);
+        let imu = ScaledImu::from_sample(&sample(16384, 0, 1310), g2, dps250, Variant::Mpu6000, 7);
+        assert_eq!((imu.time_boot_ms, imu.xacc, imu.yacc, imu.zacc), (7, 1000, -1000, 0));
+        // 10°/s
+        assert_eq!((imu.xgyro, imu.ygyro, imu.zgyro), (174, -174, 0));
+        assert_eq!(imu.temperature, 3653);
+
+        let dps2000 = GyroRange::DPS2000;
+        let imu = ScaledImu::from_sample(&sample(0, 0, 32767), g2, dps2000, Variant::Mpu6500, 0);
+        assert_eq!((imu.xgyro, imu.ygyro), (i16::MAX, i16::MIN));
+        assert_eq!(imu.temperature, 2100);
+    }
+
+    #[test]
+    fn test_raw_imu() {
+        use super::RawImu;
+
+        let imu = RawImu::from_sample(&sample(100, 0, -5), Variant::Mpu9250, 42);
+        assert_eq!((imu.time_usec, imu.xacc, imu.yacc, imu.xgyro), (42, 100, -100, -5));
+        assert_eq!(imu.temperature, 2100);
+        // 0°C is sent as 0.01°C
+        assert_eq!(RawImu::from_sample(&sample(0, -12420, 0), Variant::Mpu6000, 0).temperature, 1);
+        assert_eq!(RawImu::from_sample(&sample(0, -7011, 0), Variant::Mpu6500, 0).temperature, 1);
+    }
+}
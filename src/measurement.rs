@@ -1,12 +1,70 @@
+use core::fmt;
+
+use crate::attitude::LevelTrim;
 use crate::registers::{AccelerometerRange, GyroRange};
+use crate::scalar::{self, Scalar};
+use crate::variant::Variant;
+
+fn magnitude(counts: [i16; 3]) -> f32 {
+    let [x, y, z] = [counts[0] as f32, counts[1] as f32, counts[2] as f32];
+    libm::sqrtf(x * x + y * y + z * z)
+}
+
+fn is_full_scale(value: i16) -> bool {
+    value == i16::MAX || value <= -i16::MAX
+}
 
+/// Counts to thousandths of the unit of `ratio`, rounded to nearest in integer math
+pub(crate) fn to_milli(counts: [i16; 3], (numerator, denominator): (i32, i32)) -> [i32; 3] {
+    let scale = |count: i16| {
+        let scaled = count as i32 * 1000 * denominator;
+        (scaled + scaled.signum() * numerator / 2) / numerator
+    };
+    [scale(counts[0]), scale(counts[1]), scale(counts[2])]
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Acceleration(pub i16, pub i16, pub i16);
 
 impl Acceleration {
+    /// Whether any axis reads full scale (±32767), i.e. the sample is saturated
+    pub fn is_clipped(&self) -> bool {
+        self.clipped_axes().iter().any(|&clipped| clipped)
+    }
+
+    pub fn clipped_axes(&self) -> [bool; 3] {
+        [is_full_scale(self.0), is_full_scale(self.1), is_full_scale(self.2)]
+    }
+
+    pub fn to_array(self) -> [i16; 3] {
+        [self.0, self.1, self.2]
+    }
+
     pub fn normalize(self, range: AccelerometerRange) -> (f32, f32, f32) {
         let div: f32 = range.scale_factor();
         (self.0 as f32 / div, self.1 as f32 / div, self.2 as f32 / div)
     }
+
+    /// `normalize` computed in `T`, e.g. `f64`
+    pub fn normalize_as<T: Scalar>(self, range: AccelerometerRange) -> (T, T, T) {
+        scalar::scale(self.to_array(), range.scale_factor())
+    }
+
+    /// Milli-g per axis in integer math
+    pub fn to_mg(self, range: AccelerometerRange) -> [i32; 3] {
+        to_milli(self.to_array(), range.scale_ratio())
+    }
+
+    /// Vector length in counts, divide by the range scale factor for g
+    pub fn magnitude(&self) -> f32 {
+        magnitude(self.to_array())
+    }
+
+    /// (pitch, roll) in degrees from gravity, only meaningful at rest
+    pub fn tilt_angles(&self) -> (f32, f32) {
+        let trim = LevelTrim::from_gravity((self.0 as f32, self.1 as f32, self.2 as f32));
+        (trim.pitch, trim.roll)
+    }
 }
 
 impl From<&[i16]> for Acceleration {
@@ -25,13 +83,80 @@ impl From<&[u8]> for Acceleration {
     }
 }
 
+impl From<[i16; 3]> for Acceleration {
+    fn from([x, y, z]: [i16; 3]) -> Self {
+        Self(x, y, z)
+    }
+}
+
+impl From<Acceleration> for [i16; 3] {
+    fn from(value: Acceleration) -> Self {
+        value.to_array()
+    }
+}
+
+impl From<Acceleration> for [f32; 3] {
+    fn from(value: Acceleration) -> Self {
+        [value.0 as f32, value.1 as f32, value.2 as f32]
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<Acceleration> for mint::Vector3<i16> {
+    fn from(value: Acceleration) -> Self {
+        value.to_array().into()
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<Acceleration> for mint::Vector3<f32> {
+    fn from(value: Acceleration) -> Self {
+        <[f32; 3]>::from(value).into()
+    }
+}
+
+impl fmt::Display for Acceleration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.0, self.1, self.2)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Gyro(pub i16, pub i16, pub i16);
 
 impl Gyro {
+    /// Whether any axis reads full scale (±32767), i.e. the sample is saturated
+    pub fn is_clipped(&self) -> bool {
+        self.clipped_axes().iter().any(|&clipped| clipped)
+    }
+
+    pub fn clipped_axes(&self) -> [bool; 3] {
+        [is_full_scale(self.0), is_full_scale(self.1), is_full_scale(self.2)]
+    }
+
+    pub fn to_array(self) -> [i16; 3] {
+        [self.0, self.1, self.2]
+    }
+
     pub fn normalize(self, range: GyroRange) -> (f32, f32, f32) {
         let div: f32 = range.scale_factor();
         (self.0 as f32 / div, self.1 as f32 / div, self.2 as f32 / div)
     }
+
+    /// `normalize` computed in `T`, e.g. `f64`
+    pub fn normalize_as<T: Scalar>(self, range: GyroRange) -> (T, T, T) {
+        scalar::scale(self.to_array(), range.scale_factor())
+    }
+
+    /// Milli-degrees per second per axis in integer math
+    pub fn to_mdps(self, range: GyroRange) -> [i32; 3] {
+        to_milli(self.to_array(), range.scale_ratio())
+    }
+
+    /// Total rotation rate in counts, divide by the range scale factor for °/s
+    pub fn magnitude(&self) -> f32 {
+        magnitude(self.to_array())
+    }
 }
 
 impl From<&[i16]> for Gyro {
@@ -50,7 +175,105 @@ impl From<&[u8]> for Gyro {
     }
 }
 
+impl From<[i16; 3]> for Gyro {
+    fn from([x, y, z]: [i16; 3]) -> Self {
+        Self(x, y, z)
+    }
+}
+
+impl From<Gyro> for [i16; 3] {
+    fn from(value: Gyro) -> Self {
+        value.to_array()
+    }
+}
+
+impl From<Gyro> for [f32; 3] {
+    fn from(value: Gyro) -> Self {
+        [value.0 as f32, value.1 as f32, value.2 as f32]
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<Gyro> for mint::Vector3<i16> {
+    fn from(value: Gyro) -> Self {
+        value.to_array().into()
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<Gyro> for mint::Vector3<f32> {
+    fn from(value: Gyro) -> Self {
+        <[f32; 3]>::from(value).into()
+    }
+}
+
+impl fmt::Display for Gyro {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.0, self.1, self.2)
+    }
+}
+
+/// One burst of accelerometer, temperature and gyro registers
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ImuSample {
+    pub acceleration: Acceleration,
+    pub temperature: Temperature,
+    pub gyro: Gyro,
+}
+
+impl From<(Acceleration, Temperature, Gyro)> for ImuSample {
+    fn from((acceleration, temperature, gyro): (Acceleration, Temperature, Gyro)) -> Self {
+        Self { acceleration, temperature, gyro }
+    }
+}
+
+/// Whether a sample holds a new measurement, see `MPU6000::read_sample_freshness`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Freshness {
+    Fresh,
+    /// Same measurement as the previous read, the output data rate has not caught up
+    Stale,
+}
+
+impl Freshness {
+    pub fn is_fresh(&self) -> bool {
+        *self == Freshness::Fresh
+    }
+}
+
+/// Mean of several bursts in g, °C and °/s, see `MPU6000::sample_mean`
 #[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SampleMean {
+    pub acceleration: (f32, f32, f32),
+    pub temperature: f32,
+    pub gyro: (f32, f32, f32),
+}
+
+/// Per-axis counts of full-scale samples seen by the driver
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ClipStats {
+    pub accelerometer: [u32; 3],
+    pub gyro: [u32; 3],
+}
+
+fn count_clipped(counters: &mut [u32; 3], clipped: [bool; 3]) {
+    for (counter, clipped) in counters.iter_mut().zip(clipped.iter()) {
+        *counter = counter.saturating_add(*clipped as u32);
+    }
+}
+
+impl ClipStats {
+    pub(crate) fn record_acceleration(&mut self, acceleration: &Acceleration) {
+        count_clipped(&mut self.accelerometer, acceleration.clipped_axes())
+    }
+
+    pub(crate) fn record_gyro(&mut self, gyro: &Gyro) {
+        count_clipped(&mut self.gyro, gyro.clipped_axes())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Temperature(pub i16);
 
 impl From<&[u8]> for Temperature {
@@ -64,13 +287,183 @@ impl Temperature {
         Self((high as i16) << 8 | low as i16)
     }
 
+    /// MPU6000 and MPU6050 conversion, see `centi_celcius_for` for other variants
+    #[deprecated(note = "MPU6000/6050 conversion only, use `centi_celcius_for`")]
     pub fn centi_celcius(self) -> i16 {
+        self.mpu6000_centi_celcius()
+    }
+
+    fn mpu6000_centi_celcius(self) -> i16 {
         (self.0 as i32 * 100 / 340 + 3653) as i16
     }
+
+    pub fn centi_celcius_for(self, variant: Variant) -> i16 {
+        libm::roundf(self.celsius(variant) * 100.0) as i16
+    }
+
+    /// Degrees celsius using the conversion constants of `variant`
+    pub fn celsius(self, variant: Variant) -> f32 {
+        self.0 as f32 / variant.temperature_sensitivity() + variant.temperature_offset()
+    }
+
+    /// Formats like `Display` with the conversion of `variant`
+    pub fn display_for(self, variant: Variant) -> CelsiusDisplay {
+        CelsiusDisplay(self.centi_celcius_for(variant))
+    }
 }
 
-impl Into<f32> for Temperature {
-    fn into(self) -> f32 {
-        self.0 as f32 / 340.0 + 365.3
+/// Formats as degrees celcius with two decimals using the MPU6000 and MPU6050
+/// conversion, e.g. `36.53°C`, see `Temperature::display_for` for other variants
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&CelsiusDisplay(self.mpu6000_centi_celcius()), f)
+    }
+}
+
+/// Centi-degrees celcius formatted with two decimals, see `Temperature::display_for`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CelsiusDisplay(pub i16);
+
+impl fmt::Display for CelsiusDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let centi = self.0 as i32;
+        let sign = if centi < 0 { "-" } else { "" };
+        write!(f, "{}{}.{:02}°C", sign, centi.abs() / 100, centi.abs() % 100)
+    }
+}
+
+/// °C with the MPU6000 and MPU6050 conversion, see `Temperature::celsius` for other
+/// variants
+impl From<Temperature> for f32 {
+    fn from(temperature: Temperature) -> f32 {
+        temperature.celsius(Variant::Mpu6000)
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDebug for Acceleration {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        f.debug_tuple("Acceleration")?.field(&self.0)?.field(&self.1)?.field(&self.2)?.finish()
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for Acceleration {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        ufmt::uwrite!(f, "({}, {}, {})", self.0, self.1, self.2)
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDebug for Gyro {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        f.debug_tuple("Gyro")?.field(&self.0)?.field(&self.1)?.field(&self.2)?.finish()
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for Gyro {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        ufmt::uwrite!(f, "({}, {}, {})", self.0, self.1, self.2)
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDebug for Temperature {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        f.debug_tuple("Temperature")?.field(&self.0)?.finish()
+    }
+}
+
+/// Formats as degrees celcius with two decimals using the MPU6000 and MPU6050
+/// conversion, e.g. `36.53°C`, see `Temperature::display_for` for other variants
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for Temperature {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        ufmt::uDisplay::fmt(&CelsiusDisplay(self.mpu6000_centi_celcius()), f)
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for CelsiusDisplay {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        let centi = self.0 as i32;
+        if centi < 0 {
+            f.write_char('-')?;
+        }
+        let (integer, fraction) = (centi.abs() / 100, centi.abs() % 100);
+        let padding = if fraction < 10 { "0" } else { "" };
+        ufmt::uwrite!(f, "{}.{}{}°C", integer, padding, fraction)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_display() {
+        extern crate std;
+        use std::string::ToString;
+
+        use super::{Acceleration, Temperature};
+        use crate::variant::Variant;
+
+        assert_eq!(Acceleration(1, -2, 3).to_string(), "(1, -2, 3)");
+        assert_eq!(Temperature(0).to_string(), "36.53°C");
+        assert_eq!(Temperature(-12610).to_string(), "-0.55°C");
+        assert_eq!(Temperature(3339).display_for(Variant::Mpu6500).to_string(), "31.00°C");
+        assert_eq!(Temperature(-7345).display_for(Variant::Mpu9250).to_string(), "-1.00°C");
+    }
+
+    #[test]
+    fn test_temperature_variants() {
+        use super::Temperature;
+        use crate::variant::Variant;
+
+        assert_eq!(f32::from(Temperature(340)), 37.53);
+        assert_eq!(Temperature(0).centi_celcius_for(Variant::Mpu9250), 2100);
+        assert_eq!(Temperature(3339).centi_celcius_for(Variant::Mpu6500), 3100);
+    }
+
+    #[test]
+    fn test_integer_units() {
+        use super::{Acceleration, Gyro};
+        use crate::registers::{AccelerometerRange, GyroRange};
+
+        assert_eq!(Acceleration(2048, -1024, 3).to_mg(AccelerometerRange::G16), [1000, -500, 1]);
+        assert_eq!(Gyro(655, -131, 32767).to_mdps(GyroRange::DPS500), [10000, -2000, 500260]);
+        assert_eq!(Gyro(i16::MIN, 0, 1).to_mdps(GyroRange::DPS2000), [-1998049, 0, 61]);
+    }
+
+    #[test]
+    fn test_magnitude_and_tilt() {
+        use super::{Acceleration, Gyro};
+
+        assert_eq!(Acceleration(3, 4, 12).magnitude(), 13.0);
+        assert_eq!(Gyro(0, -5, 0).magnitude(), 5.0);
+        let (pitch, roll) = Acceleration(0, 2048, 2048).tilt_angles();
+        assert!(pitch.abs() < 1e-4 && (roll - 45.0).abs() < 1e-4);
+        let (pitch, _) = Acceleration(-2048, 0, 0).tilt_angles();
+        assert!((pitch - 90.0).abs() < 1e-4);
     }
 }
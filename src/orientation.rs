@@ -0,0 +1,106 @@
+//! Sensor to board axis remapping
+
+use crate::measurement::{Acceleration, Gyro};
+
+/// Sensor mounting rotation relative to the board, named after the rotation that
+/// takes the board frame to the sensor frame. Yaw is clockwise seen from above,
+/// matching the Betaflight `CW` alignments noted on each variant.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rotation {
+    /// CW0
+    #[default]
+    None,
+    /// CW90
+    Yaw90,
+    /// CW180
+    Yaw180,
+    /// CW270
+    Yaw270,
+    /// CW180FLIP, upside down
+    Roll180,
+    /// CW270FLIP
+    Roll180Yaw90,
+    /// CW0FLIP
+    Roll180Yaw180,
+    /// CW90FLIP
+    Roll180Yaw270,
+}
+
+impl Rotation {
+    /// Sensor frame vector in the board frame, -32768 maps to 32767 when negated
+    pub fn apply(&self, vector: [i16; 3]) -> [i16; 3] {
+        let [x, y, z] = vector;
+        let (nx, ny, nz) = (x.saturating_neg(), y.saturating_neg(), z.saturating_neg());
+        match self {
+            Rotation::None => [x, y, z],
+            Rotation::Yaw90 => [y, nx, z],
+            Rotation::Yaw180 => [nx, ny, z],
+            Rotation::Yaw270 => [ny, x, z],
+            Rotation::Roll180 => [x, ny, nz],
+            Rotation::Roll180Yaw90 => [ny, nx, nz],
+            Rotation::Roll180Yaw180 => [nx, y, nz],
+            Rotation::Roll180Yaw270 => [y, x, nz],
+        }
+    }
+}
+
+/// Per axis sign flips for boards that mirror single axes
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inversion {
+    pub x: bool,
+    pub y: bool,
+    pub z: bool,
+}
+
+impl Inversion {
+    pub fn apply(&self, vector: [i16; 3]) -> [i16; 3] {
+        let flip = |value: i16, invert: bool| if invert { value.saturating_neg() } else { value };
+        [flip(vector[0], self.x), flip(vector[1], self.y), flip(vector[2], self.z)]
+    }
+}
+
+/// Complete sensor to board mapping: sign flips on the sensor axes, then the rotation
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Orientation {
+    pub rotation: Rotation,
+    pub accelerometer_inversion: Inversion,
+    pub gyro_inversion: Inversion,
+}
+
+impl Orientation {
+    pub fn acceleration(&self, acceleration: Acceleration) -> Acceleration {
+        let vector = self.accelerometer_inversion.apply(acceleration.to_array());
+        self.rotation.apply(vector).into()
+    }
+
+    pub fn gyro(&self, gyro: Gyro) -> Gyro {
+        self.rotation.apply(self.gyro_inversion.apply(gyro.to_array())).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_orientation() {
+        use super::{Inversion, Orientation, Rotation};
+        use crate::measurement::{Acceleration, Gyro};
+
+        assert_eq!(Rotation::Yaw90.apply([1, 2, 3]), [2, -1, 3]);
+        assert_eq!(Rotation::Roll180Yaw90.apply([1, 2, 3]), [-2, -1, -3]);
+        assert_eq!(Rotation::Yaw180.apply([i16::MIN, 0, 0]), [i16::MAX, 0, 0]);
+
+        let orientation = Orientation {
+            rotation: Rotation::Yaw90,
+            gyro_inversion: Inversion { x: true, ..Default::default() },
+            ..Default::default()
+        };
+        assert_eq!(orientation.gyro(Gyro(1, 2, 3)), Gyro(2, 1, 3));
+        assert_eq!(orientation.acceleration(Acceleration(1, 2, 3)), Acceleration(2, -1, 3));
+    }
+}
@@ -0,0 +1,224 @@
+//! Suspend, resume and wake on motion, preserving the device configuration
+
+use embedded_hal::blocking::delay::DelayMs;
+
+use crate::bus::RegAccess;
+use crate::encoding;
+use crate::error::Error;
+use crate::profile::Profile;
+use crate::registers::{AccelerometerHighPass, Register, WakeRate};
+use crate::{ClockSource, Interrupt, MPU6000};
+
+/// Configuration registers restored by `resume`, in write order. USER_CTRL comes
+/// last so the FIFO is only re-enabled once everything feeding it is configured.
+const SNAPSHOT_REGISTERS: [Register; 10] = [
+    Register::PowerManagement1,
+    Register::PowerManagement2,
+    Register::SampleRateDivider,
+    Register::Configuration,
+    Register::GyroConfig,
+    Register::AccelerometerConfig,
+    Register::FifoEnable,
+    Register::IntPinConfig,
+    Register::InterruptEnable,
+    Register::UserControl,
+];
+
+const SLEEP: u8 = 1 << 6;
+const CYCLE: u8 = 5;
+const TEMPERATURE_DISABLE: u8 = 3;
+/// STBY_XG, STBY_YG and STBY_ZG
+const GYRO_STANDBY: u8 = 0b111;
+/// STBY_XA, STBY_YA and STBY_ZA
+const ACCEL_STANDBY: u8 = 0b111 << 3;
+
+/// Gyro start-up time from sleep
+const WAKE_DELAY_MS: u32 = 30;
+
+/// Raw values of the configuration registers
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RegisterSnapshot([u8; SNAPSHOT_REGISTERS.len()]);
+
+impl RegisterSnapshot {
+    fn value(&self, register: Register) -> Option<u8> {
+        let index = SNAPSHOT_REGISTERS.iter().position(|&r| r == register)?;
+        Some(self.0[index])
+    }
+}
+
+impl<E, BUS: RegAccess<Error = E>, P: Profile> MPU6000<BUS, P> {
+    pub fn snapshot_registers(&mut self) -> Result<RegisterSnapshot, E> {
+        let mut values = [0u8; SNAPSHOT_REGISTERS.len()];
+        for (value, &register) in values.iter_mut().zip(SNAPSHOT_REGISTERS.iter()) {
+            *value = self.bus.read(register)?;
+        }
+        Ok(RegisterSnapshot(values))
+    }
+
+    /// Writes every register of `snapshot`, waking the device
+    pub fn restore_registers(&mut self, snapshot: &RegisterSnapshot) -> Result<(), E> {
+        for (&value, &register) in snapshot.0.iter().zip(SNAPSHOT_REGISTERS.iter()) {
+            let value = match register {
+                Register::PowerManagement1 => value & !SLEEP,
+                _ => value,
+            };
+            self.bus.write(register, value)?;
+        }
+        Ok(())
+    }
+
+    /// Snapshots the configuration and puts the device to sleep.
+    /// The device may be power gated until `resume`.
+    pub fn suspend(&mut self) -> Result<(), E> {
+        let snapshot = self.snapshot_registers()?;
+        self.set_sleep(true)?;
+        self.suspended = Some(snapshot);
+        Ok(())
+    }
+
+    /// Wakes the device and restores the configuration saved by `suspend`, then waits
+    /// for the gyro to start up. After power gating, call only once the 100ms power-up
+    /// time has passed.
+    pub fn resume<D: DelayMs<u32>>(&mut self, delay: &mut D) -> Result<(), E> {
+        match self.suspended.take() {
+            Some(snapshot) => self.restore_registers(&snapshot)?,
+            None => self.set_sleep(false)?,
+        }
+        delay.delay_ms(WAKE_DELAY_MS);
+        Ok(())
+    }
+
+    /// Low power accelerometer only mode that raises the motion interrupt when any
+    /// axis changes by more than `threshold_mg`, sampling at `wake_rate`.
+    ///
+    /// Gyros go to standby, the temperature sensor is disabled and the accelerometer
+    /// high pass filter holds the current sample as the reference. The configuration
+    /// is saved and restored by `exit_wake_on_motion`. MPU6000 and MPU6050 only.
+    pub fn enter_wake_on_motion<D: DelayMs<u32>>(
+        &mut self,
+        threshold_mg: u16,
+        wake_rate: WakeRate,
+        delay: &mut D,
+    ) -> Result<(), Error<E>> {
+        if self.variant.is_6500_class() {
+            return Err(Error::Unsupported);
+        }
+        let threshold = (threshold_mg / 2).min(u8::MAX as u16) as u8;
+        debug!("enter wake on motion, threshold {}", threshold);
+        let snapshot = self.snapshot_registers().map_err(Error::Bus)?;
+        self.enter_cycle_mode(threshold, wake_rate, delay).map_err(Error::Bus)?;
+        self.wake_on_motion = Some(snapshot);
+        Ok(())
+    }
+
+    fn enter_cycle_mode<D: DelayMs<u32>>(
+        &mut self,
+        threshold: u8,
+        wake_rate: WakeRate,
+        delay: &mut D,
+    ) -> Result<(), E> {
+        self.set_sleep(false)?;
+        self.bus.write(Register::PowerManagement2, (wake_rate as u8) << 6 | GYRO_STANDBY)?;
+        self.write_accelerometer_high_pass(AccelerometerHighPass::Reset)?;
+        self.bus.write(Register::MotionThreshold, threshold)?;
+        self.bus.write(Register::MotionDuration, 1)?;
+        self.set_interrupt_enable(Interrupt::Motion, true)?;
+        self.write_accelerometer_high_pass(AccelerometerHighPass::Hz5)?;
+        delay.delay_ms(1);
+        self.write_accelerometer_high_pass(AccelerometerHighPass::Hold)?;
+        self.set_register(Register::PowerManagement1, TEMPERATURE_DISABLE, 1, 1)?;
+        self.set_register(Register::PowerManagement1, CYCLE, 1, 1)
+    }
+
+    /// Leaves wake on motion, restoring the configuration saved on entry
+    pub fn exit_wake_on_motion<D: DelayMs<u32>>(&mut self, delay: &mut D) -> Result<(), E> {
+        debug!("exit wake on motion");
+        match self.wake_on_motion.take() {
+            Some(snapshot) => self.restore_registers(&snapshot)?,
+            None => {
+                self.set_register(Register::PowerManagement1, CYCLE, 1, 0)?;
+                self.set_register(Register::PowerManagement1, TEMPERATURE_DISABLE, 1, 0)?;
+                self.bus.write(Register::PowerManagement2, 0)?;
+                self.set_interrupt_enable(Interrupt::Motion, false)?;
+            }
+        }
+        delay.delay_ms(WAKE_DELAY_MS);
+        Ok(())
+    }
+
+    /// Wakes the gyros and restores the configuration saved by `enter_accel_only_mode`,
+    /// then waits for the gyro to start up
+    pub fn exit_accel_only_mode<D: DelayMs<u32>>(&mut self, delay: &mut D) -> Result<(), E> {
+        debug!("exit accel only mode");
+        self.exit_sensor_standby()?;
+        delay.delay_ms(WAKE_DELAY_MS);
+        Ok(())
+    }
+
+    /// Puts all accelerometer axes in standby, keeping the gyros, temperature sensor,
+    /// clock source and sample rate. The accelerometer draws about 500µA of the
+    /// 3.9mA the device needs with everything on.
+    ///
+    /// Accelerometer readings are meaningless until `exit_gyro_only_mode`, which
+    /// restores the configuration saved on entry.
+    pub fn enter_gyro_only_mode(&mut self) -> Result<(), E> {
+        debug!("enter gyro only mode");
+        let snapshot = self.snapshot_registers()?;
+        self.set_sleep(false)?;
+        self.set_register(Register::PowerManagement1, CYCLE, 1, 0)?;
+        self.bus.write(Register::PowerManagement2, ACCEL_STANDBY)?;
+        self.sensor_standby = Some(snapshot);
+        Ok(())
+    }
+
+    /// Wakes the accelerometer and restores the configuration saved by
+    /// `enter_gyro_only_mode`
+    pub fn exit_gyro_only_mode(&mut self) -> Result<(), E> {
+        debug!("exit gyro only mode");
+        self.exit_sensor_standby()
+    }
+
+    fn exit_sensor_standby(&mut self) -> Result<(), E> {
+        match self.sensor_standby.take() {
+            Some(snapshot) => {
+                self.restore_registers(&snapshot)?;
+                match snapshot.value(Register::SampleRateDivider) {
+                    Some(divider) => self.write_sample_rate_divider(divider),
+                    None => Ok(()),
+                }
+            }
+            None => {
+                self.bus.write(Register::PowerManagement2, 0)?;
+                self.select_best_clock().map(|_| ())
+            }
+        }
+    }
+}
+
+/// Accelerometer only operation, for products that only need orientation
+impl<E, BUS: RegAccess<Error = E>> MPU6000<BUS> {
+    /// Puts all gyro axes in standby and runs from the internal oscillator, keeping
+    /// the accelerometer and temperature sensor at `rate` Hz. Saves about 3.5mA.
+    ///
+    /// Gyro readings are meaningless until `exit_accel_only_mode`, which restores the
+    /// configuration saved on entry. Rates the divider cannot reach are rejected with
+    /// `Error::InvalidArgument`.
+    pub fn enter_accel_only_mode(&mut self, rate: u16) -> Result<(), Error<E>> {
+        let divider =
+            encoding::sample_rate_divider(self.dlpf, rate).ok_or(Error::InvalidArgument)?;
+        debug!("enter accel only mode at {}Hz", rate);
+        let snapshot = self.snapshot_registers().map_err(Error::Bus)?;
+        self.enter_accel_only(divider).map_err(Error::Bus)?;
+        self.sensor_standby = Some(snapshot);
+        Ok(())
+    }
+
+    fn enter_accel_only(&mut self, divider: u8) -> Result<(), E> {
+        self.set_sleep(false)?;
+        self.set_register(Register::PowerManagement1, CYCLE, 1, 0)?;
+        self.set_register(Register::PowerManagement1, TEMPERATURE_DISABLE, 1, 0)?;
+        self.set_clock_source(ClockSource::Internal)?;
+        self.bus.write(Register::PowerManagement2, GYRO_STANDBY)?;
+        self.write_sample_rate_divider(divider)
+    }
+}
@@ -0,0 +1,37 @@
+//! Compile-time configuration profiles
+//!
+//! A profile fixes some or all of the measurement configuration in the driver type,
+//! so scale factors become constants the optimizer can fold into conversion math.
+//! `Runtime` fixes nothing and keeps every setter available, it is the default.
+
+use crate::registers::{AccelerometerRange, GyroRange};
+
+pub trait Profile {
+    const ACCELEROMETER_RANGE: Option<AccelerometerRange>;
+    const GYRO_RANGE: Option<GyroRange>;
+    const DLPF: Option<u8>;
+    const SAMPLE_RATE_DIVIDER: Option<u8>;
+}
+
+/// Everything configured at runtime through the driver setters
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Runtime;
+
+impl Profile for Runtime {
+    const ACCELEROMETER_RANGE: Option<AccelerometerRange> = None;
+    const GYRO_RANGE: Option<GyroRange> = None;
+    const DLPF: Option<u8> = None;
+    const SAMPLE_RATE_DIVIDER: Option<u8> = None;
+}
+
+/// Profile from register field values: AFS_SEL, FS_SEL, DLPF_CFG and SMPLRT_DIV,
+/// e.g. `Fixed<3, 3, 1, 0>` is +/- 16g, +/- 2000°/s, 188Hz DLPF at 1kHz
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Fixed<const ACCELEROMETER: u8, const GYRO: u8, const DLPF: u8, const DIVIDER: u8>;
+
+impl<const A: u8, const G: u8, const D: u8, const S: u8> Profile for Fixed<A, G, D, S> {
+    const ACCELEROMETER_RANGE: Option<AccelerometerRange> = AccelerometerRange::from_bits(A);
+    const GYRO_RANGE: Option<GyroRange> = GyroRange::from_bits(G);
+    const DLPF: Option<u8> = Some(D & 0x7);
+    const SAMPLE_RATE_DIVIDER: Option<u8> = Some(S);
+}
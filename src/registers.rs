@@ -1,6 +1,44 @@
 //! All constants used in the driver, mostly register addresses
 
+/// Raw value not matching any variant of a register value enum
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InvalidValue(pub u8);
+
+/// `TryFrom<u8>` and `From<Self> for u8` for a fieldless enum, decoding exactly the
+/// listed variants
+macro_rules! register_value {
+    ($name:ident { $($variant:ident),+ $(,)? }) => {
+        impl core::convert::TryFrom<u8> for $name {
+            type Error = $crate::registers::InvalidValue;
+
+            fn try_from(value: u8) -> Result<Self, Self::Error> {
+                $(
+                    if value == $name::$variant as u8 {
+                        return Ok($name::$variant);
+                    }
+                )+
+                Err($crate::registers::InvalidValue(value))
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(value: $name) -> u8 {
+                value as u8
+            }
+        }
+
+        impl From<&$name> for u8 {
+            fn from(value: &$name) -> u8 {
+                *value as u8
+            }
+        }
+    };
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum ClockSelection {
+    #[default]
     Internal8Mhz = 0,
     PLLWithXAxisGyroRef = 1,
     PLLWithYAxisGyroRef = 2,
@@ -10,19 +48,128 @@ pub enum ClockSelection {
     StopClock = 7,
 }
 
+register_value!(ClockSelection {
+    Internal8Mhz,
+    PLLWithXAxisGyroRef,
+    PLLWithYAxisGyroRef,
+    PLLWithZAxisGyroRef,
+    PLLWithExternal32_768KHZ,
+    PLLWithExternal19_2MHZ,
+    StopClock
+});
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PowerManagement1 {
     DeviceReset = 1 << 7,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SignalPathReset {
     GyroReset = 1 << 2,
     AccelerometerReset = 1 << 1,
     TemperatureReset = 1,
 }
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+/// EXT_SYNC_SET, sensor output whose LSB latches the FSYNC pin state
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExtSync {
+    #[default]
+    Disabled = 0,
+    TemperatureLow = 1,
+    GyroXLow = 2,
+    GyroYLow = 3,
+    GyroZLow = 4,
+    AccelerometerXLow = 5,
+    AccelerometerYLow = 6,
+    AccelerometerZLow = 7,
+}
+
+register_value!(ExtSync {
+    Disabled,
+    TemperatureLow,
+    GyroXLow,
+    GyroYLow,
+    GyroZLow,
+    AccelerometerXLow,
+    AccelerometerYLow,
+    AccelerometerZLow
+});
+
+/// FCHOICE_B, gyro DLPF bypass on MPU6500 class parts
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GyroBandwidth {
+    /// Bandwidth and rate set by DLPF_CFG and SMPLRT_DIV
+    #[default]
+    Dlpf = 0,
+    /// 8800Hz bandwidth, 32kHz output
+    Hz8800 = 1,
+    /// 3600Hz bandwidth, 32kHz output
+    Hz3600 = 2,
+}
+
+register_value!(GyroBandwidth { Dlpf, Hz8800, Hz3600 });
+
+/// ACCEL_FCHOICE_B and A_DLPF_CFG of ACCEL_CONFIG2 on MPU6500 class parts,
+/// named by 3dB bandwidth
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AccelerometerDlpf {
+    #[default]
+    Hz218 = 1,
+    Hz99 = 2,
+    Hz45 = 3,
+    Hz21 = 4,
+    Hz10 = 5,
+    Hz5 = 6,
+    Hz420 = 7,
+    /// 1046Hz bandwidth, 4kHz output
+    Bypass = 8,
+}
+
+register_value!(AccelerometerDlpf { Hz218, Hz99, Hz45, Hz21, Hz10, Hz5, Hz420, Bypass });
+
+/// LP_WAKE_CTRL, accelerometer only low power wake up frequency
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WakeRate {
+    #[default]
+    Hz1_25 = 0,
+    Hz5 = 1,
+    Hz20 = 2,
+    Hz40 = 3,
+}
+
+register_value!(WakeRate { Hz1_25, Hz5, Hz20, Hz40 });
+
+/// ACCEL_HPF, digital high pass filter in front of the motion detector
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AccelerometerHighPass {
+    #[default]
+    Reset = 0,
+    Hz5 = 1,
+    Hz2_5 = 2,
+    Hz1_25 = 3,
+    Hz0_63 = 4,
+    /// Holds the current sample, later samples are compared against it
+    Hold = 7,
+}
+
+register_value!(AccelerometerHighPass { Reset, Hz5, Hz2_5, Hz1_25, Hz0_63, Hold });
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AccelerometerRange {
     /// +/- 2g, 16384 LSB/g
+    #[default]
     G2 = 0,
     /// +/- 4g, 8192 LSB/g
     G4 = 1,
@@ -32,8 +179,36 @@ pub enum AccelerometerRange {
     G16 = 3,
 }
 
+register_value!(AccelerometerRange { G2, G4, G8, G16 });
+
 impl AccelerometerRange {
-    pub fn scale_factor(&self) -> f32 {
+    /// Decodes the AFS_SEL field value
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0 => Some(AccelerometerRange::G2),
+            1 => Some(AccelerometerRange::G4),
+            2 => Some(AccelerometerRange::G8),
+            3 => Some(AccelerometerRange::G16),
+            _ => None,
+        }
+    }
+
+    /// LSB per g
+    pub const fn sensitivity(&self) -> u16 {
+        match self {
+            AccelerometerRange::G2 => 16384,
+            AccelerometerRange::G4 => 8192,
+            AccelerometerRange::G8 => 4096,
+            AccelerometerRange::G16 => 2048,
+        }
+    }
+
+    /// LSB per g as numerator and denominator, for exact integer conversions
+    pub const fn scale_ratio(&self) -> (i32, i32) {
+        (self.sensitivity() as i32, 1)
+    }
+
+    pub const fn scale_factor(&self) -> f32 {
         match self {
             AccelerometerRange::G2 => 16384.0,
             AccelerometerRange::G4 => 8192.0,
@@ -41,11 +216,37 @@ impl AccelerometerRange {
             AccelerometerRange::G16 => 2048.0,
         }
     }
+
+    /// Next larger range, `None` at +/- 16g
+    pub fn larger(&self) -> Option<Self> {
+        match self {
+            AccelerometerRange::G2 => Some(AccelerometerRange::G4),
+            AccelerometerRange::G4 => Some(AccelerometerRange::G8),
+            AccelerometerRange::G8 => Some(AccelerometerRange::G16),
+            AccelerometerRange::G16 => None,
+        }
+    }
+
+    /// Next smaller range, `None` at +/- 2g
+    pub fn smaller(&self) -> Option<Self> {
+        match self {
+            AccelerometerRange::G2 => None,
+            AccelerometerRange::G4 => Some(AccelerometerRange::G2),
+            AccelerometerRange::G8 => Some(AccelerometerRange::G4),
+            AccelerometerRange::G16 => Some(AccelerometerRange::G8),
+        }
+    }
 }
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+/// Divisor of `GyroRange::sensitivity`, gyro sensitivities have one decimal
+pub const GYRO_SENSITIVITY_DENOMINATOR: u16 = 10;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GyroRange {
     /// +/- 250°/s,  131 LSB/°/s
+    #[default]
     DPS250 = 0,
     /// +/- 500°/s, 65.5 LSB/°/s
     DPS500 = 1,
@@ -55,8 +256,36 @@ pub enum GyroRange {
     DPS2000 = 3,
 }
 
+register_value!(GyroRange { DPS250, DPS500, DPS1000, DPS2000 });
+
 impl GyroRange {
-    pub fn scale_factor(&self) -> f32 {
+    /// Decodes the FS_SEL field value
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0 => Some(GyroRange::DPS250),
+            1 => Some(GyroRange::DPS500),
+            2 => Some(GyroRange::DPS1000),
+            3 => Some(GyroRange::DPS2000),
+            _ => None,
+        }
+    }
+
+    /// LSB per °/s times `GYRO_SENSITIVITY_DENOMINATOR`, e.g. 655 for 65.5 LSB/°/s
+    pub const fn sensitivity(&self) -> u16 {
+        match self {
+            GyroRange::DPS250 => 1310,
+            GyroRange::DPS500 => 655,
+            GyroRange::DPS1000 => 328,
+            GyroRange::DPS2000 => 164,
+        }
+    }
+
+    /// LSB per °/s as numerator and denominator, e.g. 655/10 for 65.5 LSB/°/s
+    pub const fn scale_ratio(&self) -> (i32, i32) {
+        (self.sensitivity() as i32, GYRO_SENSITIVITY_DENOMINATOR as i32)
+    }
+
+    pub const fn scale_factor(&self) -> f32 {
         match self {
             GyroRange::DPS250 => 131.0,
             GyroRange::DPS500 => 65.5,
@@ -64,11 +293,32 @@ impl GyroRange {
             GyroRange::DPS2000 => 16.4,
         }
     }
+
+    /// Next larger range, `None` at +/- 2000°/s
+    pub fn larger(&self) -> Option<Self> {
+        match self {
+            GyroRange::DPS250 => Some(GyroRange::DPS500),
+            GyroRange::DPS500 => Some(GyroRange::DPS1000),
+            GyroRange::DPS1000 => Some(GyroRange::DPS2000),
+            GyroRange::DPS2000 => None,
+        }
+    }
+
+    /// Next smaller range, `None` at +/- 250°/s
+    pub fn smaller(&self) -> Option<Self> {
+        match self {
+            GyroRange::DPS250 => None,
+            GyroRange::DPS500 => Some(GyroRange::DPS250),
+            GyroRange::DPS1000 => Some(GyroRange::DPS500),
+            GyroRange::DPS2000 => Some(GyroRange::DPS1000),
+        }
+    }
 }
 
 #[allow(non_camel_case_types)]
-#[derive(PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum ProductId {
+    #[default]
     Unknown,
     MPU6000ES_REV_C4,
     MPU6000ES_REV_C5,
@@ -87,7 +337,7 @@ pub enum ProductId {
 impl From<u8> for ProductId {
     fn from(value: u8) -> Self {
         match value {
-            0x14 => ProductId::MPU6000_REV_C4,
+            0x14 => ProductId::MPU6000ES_REV_C4,
             0x15 => ProductId::MPU6000ES_REV_C5,
             0x16 => ProductId::MPU6000ES_REV_D6,
             0x17 => ProductId::MPU6000ES_REV_D7,
@@ -104,16 +354,48 @@ impl From<u8> for ProductId {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+impl ProductId {
+    /// Revisions C4 and C5 report half the accelerometer sensitivity of later
+    /// revisions for the same AFS_SEL, e.g. 8192 LSB/g at +/- 2g
+    pub fn accelerometer_half_scale(&self) -> bool {
+        matches!(
+            self,
+            ProductId::MPU6000ES_REV_C4
+                | ProductId::MPU6000ES_REV_C5
+                | ProductId::MPU6000_REV_C4
+                | ProductId::MPU6000_REV_C5
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Register {
     ProductId = 0xc,
+    /// Gyro offsets, see `calibration::GYRO_OFFSET_SENSITIVITY`
+    GyroXOffsetHigh = 0x13,
+    GyroXOffsetLow = 0x14,
+    GyroYOffsetHigh = 0x15,
+    GyroYOffsetLow = 0x16,
+    GyroZOffsetHigh = 0x17,
+    GyroZOffsetLow = 0x18,
     SampleRateDivider = 0x19,
     Configuration = 0x1a,
     GyroConfig = 0x1b,
     AccelerometerConfig = 0x1c,
+    /// MPU6500 class only
+    AccelerometerConfig2 = 0x1d,
+    /// Motion detection threshold, 2mg/LSB
+    MotionThreshold = 0x1f,
+    /// Motion detection duration, 1ms/LSB
+    MotionDuration = 0x20,
     FifoEnable = 0x23,
+    /// FSYNC interrupt status in bit 7
+    I2cMasterStatus = 0x36,
     IntPinConfig = 0x37,
     InterruptEnable = 0x38,
+    /// Cleared on read unless INT_RD_CLEAR is set
+    IntStatus = 0x3a,
     AccelerometerXHigh = 0x3b,
     AccelerometerXLow = 0x3c,
     AccelerometerYHigh = 0x3d,
@@ -139,3 +421,27 @@ pub enum Register {
     FifoReadWrite = 0x74,
     WhoAmI = 0x75,
 }
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_register_value() {
+        use super::{AccelerometerDlpf, GyroRange, InvalidValue, GYRO_SENSITIVITY_DENOMINATOR};
+        use core::convert::TryFrom;
+
+        assert_eq!(GyroRange::try_from(2), Ok(GyroRange::DPS1000));
+        assert_eq!(GyroRange::try_from(4), Err(InvalidValue(4)));
+        assert_eq!(u8::from(AccelerometerDlpf::Bypass), 8);
+        assert_eq!(AccelerometerDlpf::try_from(0), Err(InvalidValue(0)));
+        for range in [GyroRange::DPS250, GyroRange::DPS500, GyroRange::DPS1000, GyroRange::DPS2000]
+        {
+            let sensitivity = range.sensitivity() as f32 / GYRO_SENSITIVITY_DENOMINATOR as f32;
+            assert_eq!(sensitivity, range.scale_factor());
+        }
+        assert_eq!(GyroRange::DPS500.scale_ratio(), (655, 10));
+        assert_eq!(crate::ClockSource::try_from(5), Ok(crate::ClockSource::PLLExternal19_2MHz));
+        assert_eq!(super::ProductId::from(0x14), super::ProductId::MPU6000ES_REV_C4);
+        assert!(super::ProductId::from(0x55).accelerometer_half_scale());
+        assert!(!super::ProductId::from(0x56).accelerometer_half_scale());
+    }
+}
@@ -0,0 +1,61 @@
+//! Host timer driven sampling for boards without the INT pin routed
+
+use embedded_hal::timer::CountDown;
+
+use crate::bus::RegAccess;
+use crate::fifo::{FifoFrame, SensorDecimation};
+use crate::measurement::ImuSample;
+use crate::profile::Profile;
+use crate::MPU6000;
+
+/// Reads one sample each time a periodic `CountDown` expires
+pub struct PeriodicSampler<BUS, P, T> {
+    mpu6000: MPU6000<BUS, P>,
+    timer: T,
+    decimation: SensorDecimation,
+}
+
+impl<E, BUS: RegAccess<Error = E>, P: Profile, T: CountDown> PeriodicSampler<BUS, P, T> {
+    /// Starts `timer` with `period`, the timer must restart itself on expiry
+    pub fn new<U: Into<T::Time>>(mpu6000: MPU6000<BUS, P>, mut timer: T, period: U) -> Self {
+        timer.start(period);
+        Self { mpu6000, timer, decimation: SensorDecimation::default() }
+    }
+
+    /// Non-blocking, returns a sample if the period elapsed since the last one
+    pub fn poll(&mut self) -> Result<Option<ImuSample>, E> {
+        match self.timer.wait() {
+            Ok(()) => Ok(Some(self.mpu6000.read_sample()?)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Rates for `poll_decimated`, restarting its phase
+    pub fn set_decimation(&mut self, decimation: SensorDecimation) {
+        self.decimation = decimation
+    }
+
+    /// Same as `poll`, leaving out accelerometer or gyro data not due under the
+    /// decimation set with `set_decimation`
+    pub fn poll_decimated(&mut self) -> Result<Option<FifoFrame>, E> {
+        let sample = match self.poll()? {
+            Some(sample) => sample,
+            None => return Ok(None),
+        };
+        let frame = FifoFrame {
+            acceleration: Some(sample.acceleration),
+            temperature: Some(sample.temperature),
+            gyro: Some(sample.gyro),
+            timestamp: None,
+        };
+        Ok(Some(self.decimation.apply(frame)))
+    }
+
+    pub fn mpu6000(&mut self) -> &mut MPU6000<BUS, P> {
+        &mut self.mpu6000
+    }
+
+    pub fn free(self) -> (MPU6000<BUS, P>, T) {
+        (self.mpu6000, self.timer)
+    }
+}
@@ -0,0 +1,46 @@
+//! Numeric types scaled readings can be produced in
+
+use core::ops::{Add, Div, Mul, Sub};
+
+/// Implemented for `f32` and `f64`, implement it for a fixed point type to read into that
+pub trait Scalar:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    fn from_i16(value: i16) -> Self;
+    fn from_f32(value: f32) -> Self;
+}
+
+impl Scalar for f32 {
+    fn from_i16(value: i16) -> Self {
+        value as f32
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+}
+
+impl Scalar for f64 {
+    fn from_i16(value: i16) -> Self {
+        value as f64
+    }
+
+    fn from_f32(value: f32) -> Self {
+        value as f64
+    }
+}
+
+pub(crate) fn scale<T: Scalar>(values: [i16; 3], scale_factor: f32) -> (T, T, T) {
+    let div = T::from_f32(scale_factor);
+    let [x, y, z] = values;
+    (T::from_i16(x) / div, T::from_i16(y) / div, T::from_i16(z) / div)
+}
+
+pub(crate) fn convert<T: Scalar>(values: (f32, f32, f32)) -> (T, T, T) {
+    (T::from_f32(values.0), T::from_f32(values.1), T::from_f32(values.2))
+}
@@ -0,0 +1,72 @@
+//! Independent per-sensor handles sharing one driver
+//!
+//! ```ignore
+//! let mpu6000 = RefCell::new(mpu6000);
+//! let (accel, gyro, thermometer) = MPU6000::split_sensors(&mpu6000);
+//! ```
+
+use core::cell::RefCell;
+
+use crate::bus::RegAccess;
+use crate::measurement::{self, Acceleration, Temperature};
+use crate::profile::Profile;
+use crate::registers::{AccelerometerRange, GyroRange};
+use crate::MPU6000;
+
+pub struct Accel<'a, BUS, P> {
+    mpu6000: &'a RefCell<MPU6000<BUS, P>>,
+}
+
+pub struct Gyro<'a, BUS, P> {
+    mpu6000: &'a RefCell<MPU6000<BUS, P>>,
+}
+
+pub struct Thermometer<'a, BUS, P> {
+    mpu6000: &'a RefCell<MPU6000<BUS, P>>,
+}
+
+impl<E, BUS: RegAccess<Error = E>, P: Profile> MPU6000<BUS, P> {
+    /// Splits a shared driver into accelerometer, gyro and thermometer handles,
+    /// each borrowing the driver only for the duration of a call
+    pub fn split_sensors(
+        mpu6000: &RefCell<Self>,
+    ) -> (Accel<'_, BUS, P>, Gyro<'_, BUS, P>, Thermometer<'_, BUS, P>) {
+        (Accel { mpu6000 }, Gyro { mpu6000 }, Thermometer { mpu6000 })
+    }
+}
+
+impl<'a, E, BUS: RegAccess<Error = E>, P: Profile> Accel<'a, BUS, P> {
+    pub fn read_raw(&self) -> Result<Acceleration, E> {
+        self.mpu6000.borrow_mut().read_accel_raw()
+    }
+
+    /// Acceleration in g
+    pub fn read(&self) -> Result<(f32, f32, f32), E> {
+        self.mpu6000.borrow_mut().read_accel()
+    }
+
+    pub fn range(&self) -> AccelerometerRange {
+        self.mpu6000.borrow().accelerometer_range()
+    }
+}
+
+impl<'a, E, BUS: RegAccess<Error = E>, P: Profile> Gyro<'a, BUS, P> {
+    pub fn read_raw(&self) -> Result<measurement::Gyro, E> {
+        self.mpu6000.borrow_mut().read_gyro_raw()
+    }
+
+    /// Angular rate in °/s
+    pub fn read(&self) -> Result<(f32, f32, f32), E> {
+        self.mpu6000.borrow_mut().read_gyro()
+    }
+
+    pub fn range(&self) -> GyroRange {
+        self.mpu6000.borrow().gyro_range()
+    }
+}
+
+impl<'a, E, BUS: RegAccess<Error = E>, P: Profile> Thermometer<'a, BUS, P> {
+    pub fn read(&self) -> Result<Temperature, E> {
+        self.mpu6000.borrow_mut().read_temperature()
+    }
+}
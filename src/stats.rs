@@ -0,0 +1,225 @@
+//! Windowed per-axis statistics for noise characterization and stillness detection
+
+type Vector = (f32, f32, f32);
+
+fn map(vector: Vector, f: impl Fn(f32) -> f32) -> Vector {
+    (f(vector.0), f(vector.1), f(vector.2))
+}
+
+fn zip(a: Vector, b: Vector, f: impl Fn(f32, f32) -> f32) -> Vector {
+    (f(a.0, b.0), f(a.1, b.1), f(a.2, b.2))
+}
+
+/// Statistics over the last `N` samples, per axis
+#[derive(Copy, Clone, Debug)]
+pub struct Stats<const N: usize> {
+    window: [Vector; N],
+    index: usize,
+    len: usize,
+}
+
+impl<const N: usize> Default for Stats<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Stats<N> {
+    pub fn new() -> Self {
+        Self { window: [(0.0, 0.0, 0.0); N], index: 0, len: 0 }
+    }
+
+    pub fn update(&mut self, sample: Vector) {
+        if N == 0 {
+            return;
+        }
+        self.window[self.index] = sample;
+        self.index = (self.index + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new()
+    }
+
+    /// Number of samples currently in the window
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    fn samples(&self) -> &[Vector] {
+        &self.window[..self.len]
+    }
+
+    pub fn mean(&self) -> Option<Vector> {
+        if self.is_empty() {
+            return None;
+        }
+        let sum = self.samples().iter().fold((0.0, 0.0, 0.0), |sum, &v| zip(sum, v, |a, b| a + b));
+        Some(map(sum, |sum| sum / self.len as f32))
+    }
+
+    /// Population variance
+    pub fn variance(&self) -> Option<Vector> {
+        let mean = self.mean()?;
+        let sum = self.samples().iter().fold((0.0, 0.0, 0.0), |sum, &v| {
+            let deviation = zip(v, mean, |a, b| a - b);
+            zip(sum, deviation, |sum, d| sum + d * d)
+        });
+        Some(map(sum, |sum| sum / self.len as f32))
+    }
+
+    pub fn std_dev(&self) -> Option<Vector> {
+        Some(map(self.variance()?, libm::sqrtf))
+    }
+
+    pub fn min(&self) -> Option<Vector> {
+        let first = *self.samples().first()?;
+        Some(self.samples().iter().fold(first, |min, &v| zip(min, v, f32::min)))
+    }
+
+    pub fn max(&self) -> Option<Vector> {
+        let first = *self.samples().first()?;
+        Some(self.samples().iter().fold(first, |max, &v| zip(max, v, f32::max)))
+    }
+
+    /// Whether the window is full and every axis varies less than `max_variance`
+    pub fn is_still(&self, max_variance: f32) -> bool {
+        match self.variance() {
+            Some((x, y, z)) => {
+                self.is_full() && x < max_variance && y < max_variance && z < max_variance
+            }
+            None => false,
+        }
+    }
+}
+
+/// Allan variance at one cluster size
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AllanBin {
+    /// Samples averaged per cluster, tau is `cluster_size / sample_rate`
+    pub cluster_size: u32,
+    /// Number of consecutive cluster pairs accumulated
+    pub differences: u32,
+    pub variance: Vector,
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct Octave {
+    sum: [f64; 3],
+    count: u32,
+    previous: Option<[f64; 3]>,
+    sum_squares: [f64; 3],
+    differences: u32,
+}
+
+/// Long duration gyro capture for Allan deviation analysis.
+///
+/// Samples are binned into non-overlapping clusters of 1, 2, 4 ... 2^(K-1) samples
+/// (K up to 32)
+/// and the squared differences of consecutive cluster averages accumulated, so only
+/// K small bins are kept in memory no matter how long the capture runs.
+#[derive(Copy, Clone, Debug)]
+pub struct AllanCapture<const K: usize> {
+    octaves: [Octave; K],
+}
+
+impl<const K: usize> Default for AllanCapture<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const K: usize> AllanCapture<K> {
+    pub fn new() -> Self {
+        Self { octaves: [Octave::default(); K] }
+    }
+
+    pub fn update(&mut self, sample: Vector) {
+        let sample = [sample.0 as f64, sample.1 as f64, sample.2 as f64];
+        for (k, octave) in self.octaves.iter_mut().enumerate() {
+            for (sum, value) in octave.sum.iter_mut().zip(sample.iter()) {
+                *sum += value;
+            }
+            octave.count += 1;
+            if octave.count < 1 << k.min(31) {
+                continue;
+            }
+            let mut average = [0.0; 3];
+            for (average, sum) in average.iter_mut().zip(octave.sum.iter()) {
+                *average = sum / octave.count as f64;
+            }
+            if let Some(previous) = octave.previous {
+                for i in 0..3 {
+                    let difference = average[i] - previous[i];
+                    octave.sum_squares[i] += difference * difference;
+                }
+                octave.differences = octave.differences.saturating_add(1);
+            }
+            octave.previous = Some(average);
+            octave.sum = [0.0; 3];
+            octave.count = 0;
+        }
+    }
+
+    /// Allan variance per cluster size, skipping sizes without a complete cluster pair yet
+    pub fn bins(&self) -> impl Iterator<Item = AllanBin> + '_ {
+        self.octaves.iter().enumerate().filter(|(_, octave)| octave.differences > 0).map(
+            |(k, octave)| {
+                let n = 2.0 * octave.differences as f64;
+                let [x, y, z] = octave.sum_squares;
+                AllanBin {
+                    cluster_size: 1 << k.min(31),
+                    differences: octave.differences,
+                    variance: ((x / n) as f32, (y / n) as f32, (z / n) as f32),
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_stats() {
+        use super::Stats;
+
+        let mut stats = Stats::<4>::new();
+        assert_eq!(stats.mean(), None);
+        for &x in [9.0, 1.0, 2.0, 3.0, 4.0].iter() {
+            stats.update((x, -x, 1.0));
+        }
+        assert_eq!(stats.mean(), Some((2.5, -2.5, 1.0)));
+        assert_eq!(stats.variance(), Some((1.25, 1.25, 0.0)));
+        assert_eq!(stats.min(), Some((1.0, -4.0, 1.0)));
+        assert_eq!(stats.max(), Some((4.0, -1.0, 1.0)));
+        assert!(!stats.is_still(1.0));
+        assert!(stats.is_still(2.0));
+    }
+
+    #[test]
+    fn test_allan_capture() {
+        use super::AllanCapture;
+
+        let mut capture = AllanCapture::<3>::new();
+        for i in 0..16 {
+            let value = if i % 2 == 0 { 1.0 } else { -1.0 };
+            capture.update((value, 0.0, 2.0));
+        }
+        let mut bins = capture.bins();
+        let bin = bins.next().unwrap();
+        assert_eq!((bin.cluster_size, bin.differences), (1, 15));
+        assert_eq!(bin.variance, (2.0, 0.0, 0.0));
+        let bin = bins.next().unwrap();
+        assert_eq!((bin.cluster_size, bin.differences), (2, 7));
+        assert_eq!(bin.variance, (0.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,162 @@
+//! Output units chosen at compile time, so readings in different units cannot be mixed
+//!
+//! ```ignore
+//! let mut imu: UnitImu<_, _, MetersPerSecond2, RadiansPerSecond> = UnitImu::new(mpu6000);
+//! let acceleration = imu.read_accel()?;
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::bus::RegAccess;
+use crate::measurement::{Acceleration, Gyro};
+use crate::profile::Profile;
+use crate::MPU6000;
+
+/// Standard gravity in m/s²
+pub const STANDARD_GRAVITY: f32 = 9.806_65;
+
+/// Three axes tagged with their unit `U`
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Vector<U> {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    unit: PhantomData<U>,
+}
+
+impl<U> Vector<U> {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z, unit: PhantomData }
+    }
+
+    pub fn to_tuple(self) -> (f32, f32, f32) {
+        (self.x, self.y, self.z)
+    }
+
+    fn scaled((x, y, z): (f32, f32, f32), factor: f32) -> Self {
+        Self::new(x * factor, y * factor, z * factor)
+    }
+}
+
+/// Register counts, as `Acceleration` and `Gyro`
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Raw;
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Gravities;
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MetersPerSecond2;
+/// Degrees per second
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dps;
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RadiansPerSecond;
+
+pub trait AccelerationUnit {
+    type Output;
+
+    fn read<E, BUS: RegAccess<Error = E>, P: Profile>(
+        mpu6000: &mut MPU6000<BUS, P>,
+    ) -> Result<Self::Output, E>;
+}
+
+pub trait AngularRateUnit {
+    type Output;
+
+    fn read<E, BUS: RegAccess<Error = E>, P: Profile>(
+        mpu6000: &mut MPU6000<BUS, P>,
+    ) -> Result<Self::Output, E>;
+}
+
+impl AccelerationUnit for Raw {
+    type Output = Acceleration;
+
+    fn read<E, BUS: RegAccess<Error = E>, P: Profile>(
+        mpu6000: &mut MPU6000<BUS, P>,
+    ) -> Result<Acceleration, E> {
+        mpu6000.read_accel_raw()
+    }
+}
+
+impl AccelerationUnit for Gravities {
+    type Output = Vector<Gravities>;
+
+    fn read<E, BUS: RegAccess<Error = E>, P: Profile>(
+        mpu6000: &mut MPU6000<BUS, P>,
+    ) -> Result<Self::Output, E> {
+        Ok(Vector::scaled(mpu6000.read_accel()?, 1.0))
+    }
+}
+
+impl AccelerationUnit for MetersPerSecond2 {
+    type Output = Vector<MetersPerSecond2>;
+
+    fn read<E, BUS: RegAccess<Error = E>, P: Profile>(
+        mpu6000: &mut MPU6000<BUS, P>,
+    ) -> Result<Self::Output, E> {
+        Ok(Vector::scaled(mpu6000.read_accel()?, STANDARD_GRAVITY))
+    }
+}
+
+impl AngularRateUnit for Raw {
+    type Output = Gyro;
+
+    fn read<E, BUS: RegAccess<Error = E>, P: Profile>(
+        mpu6000: &mut MPU6000<BUS, P>,
+    ) -> Result<Gyro, E> {
+        mpu6000.read_gyro_raw()
+    }
+}
+
+impl AngularRateUnit for Dps {
+    type Output = Vector<Dps>;
+
+    fn read<E, BUS: RegAccess<Error = E>, P: Profile>(
+        mpu6000: &mut MPU6000<BUS, P>,
+    ) -> Result<Self::Output, E> {
+        Ok(Vector::scaled(mpu6000.read_gyro()?, 1.0))
+    }
+}
+
+impl AngularRateUnit for RadiansPerSecond {
+    type Output = Vector<RadiansPerSecond>;
+
+    fn read<E, BUS: RegAccess<Error = E>, P: Profile>(
+        mpu6000: &mut MPU6000<BUS, P>,
+    ) -> Result<Self::Output, E> {
+        Ok(Vector::scaled(mpu6000.read_gyro()?, core::f32::consts::PI / 180.0))
+    }
+}
+
+/// Driver wrapper reading acceleration in `A` and angular rate in `G`
+pub struct UnitImu<BUS, P, A, G> {
+    mpu6000: MPU6000<BUS, P>,
+    units: PhantomData<(A, G)>,
+}
+
+impl<E, BUS, P, A, G> UnitImu<BUS, P, A, G>
+where
+    BUS: RegAccess<Error = E>,
+    P: Profile,
+    A: AccelerationUnit,
+    G: AngularRateUnit,
+{
+    pub fn new(mpu6000: MPU6000<BUS, P>) -> Self {
+        Self { mpu6000, units: PhantomData }
+    }
+
+    pub fn read_accel(&mut self) -> Result<A::Output, E> {
+        A::read(&mut self.mpu6000)
+    }
+
+    pub fn read_gyro(&mut self) -> Result<G::Output, E> {
+        G::read(&mut self.mpu6000)
+    }
+
+    pub fn mpu6000(&mut self) -> &mut MPU6000<BUS, P> {
+        &mut self.mpu6000
+    }
+
+    pub fn free(self) -> MPU6000<BUS, P> {
+        self.mpu6000
+    }
+}
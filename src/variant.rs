@@ -0,0 +1,137 @@
+//! Register map differences between the MPU6000 family members
+
+/// Device the driver talks to, `Mpu6000` unless set with `MPU6000::set_variant`
+/// or `MPU6000::detect_variant`
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Variant {
+    #[default]
+    Mpu6000,
+    Mpu6050,
+    Mpu6500,
+    Mpu9250,
+}
+
+impl Variant {
+    /// Variant reporting `whoami` in WHO_AM_I
+    pub fn from_whoami(whoami: u8) -> Option<Self> {
+        match whoami {
+            0x68 => Some(Variant::Mpu6000),
+            0x70 => Some(Variant::Mpu6500),
+            0x71 => Some(Variant::Mpu9250),
+            _ => None,
+        }
+    }
+
+    /// Expected WHO_AM_I value, the MPU6000 and MPU6050 share 0x68
+    pub fn whoami(&self) -> u8 {
+        match self {
+            Variant::Mpu6000 | Variant::Mpu6050 => 0x68,
+            Variant::Mpu6500 => 0x70,
+            Variant::Mpu9250 => 0x71,
+        }
+    }
+
+    /// Temperature sensor LSB/°C
+    pub fn temperature_sensitivity(&self) -> f32 {
+        match self {
+            Variant::Mpu6000 | Variant::Mpu6050 => 340.0,
+            Variant::Mpu6500 | Variant::Mpu9250 => 333.87,
+        }
+    }
+
+    /// Temperature in °C at a raw reading of 0
+    pub fn temperature_offset(&self) -> f32 {
+        match self {
+            Variant::Mpu6000 | Variant::Mpu6050 => 36.53,
+            Variant::Mpu6500 | Variant::Mpu9250 => 21.0,
+        }
+    }
+
+    /// FIFO buffer size in bytes
+    pub fn fifo_size(&self) -> u16 {
+        match self {
+            Variant::Mpu6000 | Variant::Mpu6050 => 1024,
+            Variant::Mpu6500 | Variant::Mpu9250 => 512,
+        }
+    }
+
+    /// MPU6500 register layout: FCHOICE_B, ACCEL_CONFIG2 and 32kHz gyro output
+    pub fn is_6500_class(&self) -> bool {
+        matches!(self, Variant::Mpu6500 | Variant::Mpu9250)
+    }
+}
+
+/// WHO_AM_I values `MPU6000::verify` accepts, up to 8
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WhoAmISet {
+    values: [u8; 8],
+    len: u8,
+}
+
+impl Default for WhoAmISet {
+    fn default() -> Self {
+        Self::new(0x68)
+    }
+}
+
+impl WhoAmISet {
+    /// Genuine parts and common clones: MPU6000/6050, 6050 clones reporting their
+    /// AD0 high address, MPU6500, MPU9250, MPU9255 and 0x98 clones
+    pub const KNOWN: Self = Self { values: [0x68, 0x69, 0x70, 0x71, 0x73, 0x98, 0, 0], len: 6 };
+
+    pub const fn new(whoami: u8) -> Self {
+        Self { values: [whoami, 0, 0, 0, 0, 0, 0, 0], len: 1 }
+    }
+
+    /// Values beyond the first 8 are ignored
+    pub fn from_slice(values: &[u8]) -> Self {
+        let mut set = Self { values: [0; 8], len: 0 };
+        for &value in values.iter().take(8) {
+            set = set.with(value);
+        }
+        set
+    }
+
+    /// Adds `whoami`, unchanged if already present or full
+    pub fn with(mut self, whoami: u8) -> Self {
+        if !self.contains(whoami) && (self.len as usize) < self.values.len() {
+            self.values[self.len as usize] = whoami;
+            self.len += 1;
+        }
+        self
+    }
+
+    pub fn contains(&self, whoami: u8) -> bool {
+        self.values().contains(&whoami)
+    }
+
+    pub fn values(&self) -> &[u8] {
+        &self.values[..self.len as usize]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_whoami_set() {
+        use super::WhoAmISet;
+
+        assert_eq!(WhoAmISet::default().values(), &[0x68]);
+        let set = WhoAmISet::new(0x68).with(0x70).with(0x68);
+        assert_eq!(set.values(), &[0x68, 0x70]);
+        assert!(set.contains(0x70) && !set.contains(0x71));
+        assert_eq!(WhoAmISet::from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 9]).values().len(), 8);
+        assert!(WhoAmISet::KNOWN.contains(0x98));
+    }
+
+    #[test]
+    fn test_fifo_size() {
+        use super::Variant;
+
+        assert_eq!(Variant::Mpu6050.fifo_size(), 1024);
+        assert_eq!(Variant::Mpu9250.fifo_size(), 512);
+    }
+}
@@ -0,0 +1,86 @@
+//! Vibration level from high frequency accelerometer content, e.g. to spot prop imbalance
+
+use crate::filter::{AxisFilter, Biquad};
+use crate::stats::Stats;
+
+type Vector = (f32, f32, f32);
+
+/// Per-axis RMS of the filtered accelerometer signal over the last `N` samples.
+///
+/// Feed one accelerometer sample per update, at the rate given on construction.
+/// Several meters with `band` cover separate frequency bands.
+#[derive(Copy, Clone, Debug)]
+pub struct VibrationMeter<const N: usize> {
+    filter: AxisFilter<Biquad>,
+    squares: Stats<N>,
+}
+
+impl<const N: usize> VibrationMeter<N> {
+    /// Everything above `cutoff` Hz, gravity and slow manoeuvres removed
+    pub fn new(cutoff: f32, sample_rate: f32) -> Self {
+        Self::with_filter(Biquad::highpass(cutoff, sample_rate, core::f32::consts::FRAC_1_SQRT_2))
+    }
+
+    /// Energy between `low` and `high` Hz
+    pub fn band(low: f32, high: f32, sample_rate: f32) -> Self {
+        let center = libm::sqrtf(low * high);
+        let q = center / (high - low).max(f32::EPSILON);
+        Self::with_filter(Biquad::bandpass(center, sample_rate, q))
+    }
+
+    fn with_filter(filter: Biquad) -> Self {
+        Self { filter: AxisFilter::new(filter), squares: Stats::new() }
+    }
+
+    pub fn update(&mut self, acceleration: Vector) {
+        let (x, y, z) = self.filter.update(acceleration);
+        self.squares.update((x * x, y * y, z * z));
+    }
+
+    /// RMS per axis in the unit of the input, `None` before the first sample
+    pub fn rms(&self) -> Option<Vector> {
+        let (x, y, z) = self.squares.mean()?;
+        Some((libm::sqrtf(x), libm::sqrtf(y), libm::sqrtf(z)))
+    }
+
+    /// RMS of the vector magnitude, a single figure across all axes
+    pub fn level(&self) -> Option<f32> {
+        let (x, y, z) = self.squares.mean()?;
+        Some(libm::sqrtf(x + y + z))
+    }
+
+    /// Whether the window holds `N` samples
+    pub fn is_settled(&self) -> bool {
+        self.squares.is_full()
+    }
+
+    pub fn reset(&mut self) {
+        self.filter.reset();
+        self.squares.reset();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_vibration_meter() {
+        use super::VibrationMeter;
+
+        // constant gravity plus a 250Hz +/-1 wobble on x at 1kHz
+        let mut meter = VibrationMeter::<100>::new(20.0, 1000.0);
+        let wobble = [0.0, 1.0, 0.0, -1.0];
+        for &x in wobble.iter().cycle().take(400) {
+            meter.update((x, 0.0, 1.0));
+        }
+        let (x, y, z) = meter.rms().unwrap();
+        assert!((x - libm::sqrtf(0.5)).abs() < 0.05);
+        assert_eq!(y, 0.0);
+        assert!(z < 1e-3);
+
+        let mut low_band = VibrationMeter::<100>::band(20.0, 80.0, 1000.0);
+        for &x in wobble.iter().cycle().take(400) {
+            low_band.update((x, 0.0, 1.0));
+        }
+        assert!(low_band.level().unwrap() < meter.level().unwrap() / 2.0);
+    }
+}